@@ -3,49 +3,275 @@
 use eframe::egui;
 use egui::{vec2, TextEdit};
 use egui_extras::{self, RetainedImage};
+use futures::FutureExt;
 use http::{header::AUTHORIZATION, HeaderValue};
 use league_client_connector::LeagueClientConnector;
 use reqwest::{header, ClientBuilder};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::error::Error;
 use std::io::Write;
+use std::panic::AssertUnwindSafe;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU32, Ordering},
     Arc, Mutex,
 };
+use tokio::sync::mpsc;
+
+/// Number of consecutive failed requests to the League Client before the lockfile is re-read.
+const MAX_FAILED_REQUESTS: u32 = 3;
+/// Current version of the `utils/settings.json` format, see `Settings::migrate`.
+const SETTINGS_VERSION: u32 = 4;
+/// Number of decoded images kept by an `ImageCache` besides its pinned ones.
+const IMAGE_CACHE_CAPACITY: usize = 32;
+/// How long the champion select availability of every champion is reused before it's fetched again.
+const GRID_CHAMPIONS_REFRESH: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// How long the Data Dragon patch is reused before asking for it again.
+const DATA_DRAGON_PATCH_REFRESH: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// With ban hovering on, the ban is committed once this little time is left in the ban turn.
+const BAN_COMMIT_WINDOW_MS: i64 = 5000;
+
+/// Whether League Client requests go to the in-process mock client instead, see `--mock`.
+static MOCK_LCU: AtomicBool = AtomicBool::new(false);
+
+/// How many lines the in-app Logs window keeps.
+const LOG_CAPACITY: usize = 200;
+
+/// The most recent log lines, shown in the Logs window. Filled by `log_line`.
+static LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Port the mock League Client listens on, clear of the live client API's 2999 so `--mock` also
+/// works while a game is running.
+const MOCK_LCU_PORT: u16 = 52999;
+/// Riot's root certificate, the League Client and the live client API are signed with it.
+const RIOT_CERT: &[u8] = include_bytes!("../utils/riotgames.pem");
+/// The in-game live client data API, served by the game itself rather than the League Client.
+const LIVE_CLIENT_URL: &str = "https://127.0.0.1:2999/liveclientdata/allgamedata";
+
+/// Canned League Client responses for `--mock`, keyed by scenario name. Each fixture maps request
+/// paths to the JSON the client would answer with.
+const MOCK_SCENARIOS: [(&str, &str); 3] = [
+    (
+        "ready-check",
+        include_str!("../utils/mock/ready_check.json"),
+    ),
+    ("draft", include_str!("../utils/mock/draft_pick.json")),
+    ("aram", include_str!("../utils/mock/aram.json")),
+];
+
+/// Labels of the tabs in the side panel, `GUI::active_tab` indexes into this.
+const TABS: [&str; 2] = ["Settings", "Match State"];
+
+/// Number of automation actions kept in the Match State log.
+const ACTION_LOG_LENGTH: usize = 50;
+
+/// Scales the UI Scale setting allows, on top of the display's own scaling.
+const UI_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.75..=2.0;
+
+/// Update status when GitHub can't be reached.
+const OFFLINE_STATUS: &str = "Offline — update check skipped";
 
 pub struct GUI {
     pick_ban_selection: Arc<AtomicBool>,
     rune_page_selection: Arc<AtomicBool>,
+    rune_pages: HashMap<u32, RunePage>,
+    rune_champion_text: String,
+    rune_page_text: String,
+    rune_feedback: Option<String>,
     auto_accept: Arc<AtomicBool>,
     spell_selection: Arc<AtomicBool>,
+    spell_skip_roles: Vec<String>,
+    match_log: Arc<AtomicBool>,
+    in_game_guard: Arc<AtomicBool>,
     pick_text: String,
     ban_text: String,
     text: String,
-    champion_picks: Arc<Mutex<Vec<(u32, String)>>>,
-    ban_picks: Arc<Mutex<Option<(u32, String)>>>,
+    champion_picks: Vec<(u32, String)>,
+    ban_picks: Vec<(u32, String)>,
+    pick_fallback: PickFallback,
+    secondary_picks: Vec<(u32, String)>,
+    secondary_text: String,
+    counter_ban_text: String,
+    /// Shown under the watchlist input, the ban's own error label is hidden once a ban is set.
+    counter_ban_error: Option<String>,
+    decoy_ban_text: String,
+    decoy_ban_error: Option<String>,
+    ban_rule_position: Option<String>,
+    ban_rule_queue: Option<u32>,
+    ban_rule_text: String,
+    ban_rule_error: Option<String>,
+    trade_behavior: TradeBehavior,
+    swap_behavior: SwapBehavior,
+    hover_ban: bool,
+    action_retries: u32,
+    max_picks: usize,
+    protect_picks: bool,
+    autofill_picks_only: bool,
+    playable_picks: bool,
+    live_game_stats: bool,
+    counter_ban: bool,
+    counter_bans: Vec<(u32, String)>,
+    decoy_hover: bool,
+    decoy_ban: Option<(u32, String)>,
+    skip_ban: bool,
+    winrate_picks: bool,
+    stats_url: String,
+    quiet_hours: bool,
+    quiet_hours_start: String,
+    quiet_hours_end: String,
+    queue_automation: HashMap<u32, QueueAutomation>,
+    ban_rules: Vec<BanRule>,
+    champion_notes: HashMap<u32, String>,
+    pick_mode: Option<PickMode>,
+    feature_states: [FeatureState; 4],
+    invitations: Vec<Invitation>,
+    latency: Option<std::time::Duration>,
+    region: Option<String>,
+    lobby_positions: Option<(String, String)>,
+    /// Champions this account can play, `None` until the client sent them.
+    champion_inventory: Option<HashMap<u32, Availability>>,
     champions: Vec<Champion>,
-    gameflow_status: Arc<Mutex<String>>,
+    champion_locale: Arc<Mutex<String>>,
+    downloaded_champions: Arc<Mutex<Option<Vec<Champion>>>>,
+    refresh_champions: Arc<AtomicBool>,
+    champion_data_outdated: bool,
+    summoner_spells: Arc<Mutex<Vec<SummonerSpell>>>,
+    /// The spell mapping the worker last got, to only send it again once it changed.
+    sent_summoner_spells: Vec<SummonerSpell>,
+    downloaded_spell_icons: Arc<Mutex<Vec<(String, Vec<u8>)>>>,
+    gameflow_status: String,
     update: Arc<AtomicBool>,
-    images: HashMap<String, RetainedImage>,
-    selected_image1: Arc<Mutex<Option<String>>>,
-    selected_image2: Arc<Mutex<Option<String>>>,
+    /// Virtual key pressed with Ctrl+Shift for the kill switch, 0 if none is set.
+    kill_switch_code: Arc<AtomicU32>,
+    /// Set by the kill switch, shows the banner until dismissed.
+    automation_killed: Arc<AtomicBool>,
+    images: ImageCache,
+    selected_image1: Option<String>,
+    selected_image2: Option<String>,
     no_icon_img: RetainedImage,
-    assigned_role: Arc<Mutex<Option<String>>>,
+    /// Icons of the champions shown for the enemy team, named by champion id. Kept apart from
+    /// `images`, whose names are the spell choices.
+    champion_icons: ImageCache,
+    assigned_role: Option<String>,
+    /// The raw gameflow phase, see [`Event::Phase`].
+    phase: Option<String>,
+    champ_select_session: Option<serde_json::Value>,
+    action_log: VecDeque<String>,
+    debug_mode: bool,
+    always_on_top: bool,
+    minimize_on_close: bool,
+    remember_window_position: bool,
+    ui_scale: f32,
+    battery_saver: bool,
+    kill_switch_key: String,
+    practice_tool_cheats: bool,
+    window_position: Option<(f32, f32)>,
+    active_account: Option<(String, String)>,
+    per_account_picks: bool,
+    show_champion_alias: bool,
+    account_profiles: HashMap<String, AccountProfile>,
 
     connection_status: Arc<Mutex<Option<String>>>,
     update_status: Arc<Mutex<String>>,
     current_version: Arc<Mutex<String>>,
+    release_notes: Arc<Mutex<Option<String>>>,
     asset_name: Arc<Mutex<String>>,
     active_tab: usize,
+    saved_settings: Settings,
+    saved_ui_state: UiState,
+    commands: mpsc::UnboundedSender<Command>,
+    events: mpsc::UnboundedReceiver<Event>,
 
     update_button_clicked: bool,
+    quit_requested: bool,
+    minimize_requested: bool,
+    restart_error: Option<String>,
+    confirm_reset: bool,
+    diagnostics_status: Option<String>,
+    spell_editor_open: bool,
+    logs_open: bool,
+    spell_editor_status: Option<String>,
+    plan_window_open: bool,
+    owned_window_open: bool,
+    owned_selection: HashSet<u32>,
+    /// Screen area of the plan grid, cropped out of the next screenshot when exporting.
+    plan_rect: Option<egui::Rect>,
+    plan_export_requested: bool,
+    plan_export_status: Option<String>,
+    pixels_per_point: f32,
+    reset_requested: bool,
+    startup_problems: Vec<String>,
     clear_label_timer: Option<std::time::Instant>,
+    /// Picks, ban and ban skip from before the last clear, restorable while the label shows.
+    cleared_picks: Option<(Vec<(u32, String)>, Vec<(u32, String)>, bool)>,
     pick_not_found_label_timer: Option<std::time::Instant>,
+    /// Set once a pick or ban was entered, the active entry box then keeps the keyboard.
+    entry_focus: bool,
     ban_not_found_label_timer: Option<std::time::Instant>,
 }
 
+/// Sent from the UI to the automation worker.
+enum Command {
+    /// The configuration changed, the worker acts on this copy from now on.
+    ApplySettings(Settings),
+    /// The champion list was refreshed from Data Dragon.
+    Champions(Vec<Champion>),
+    /// The summoner spell id mapping changed, from Data Dragon or the spell editor.
+    SummonerSpells(Vec<SummonerSpell>),
+    /// Read the client's current rune page and send it back as `Event::CapturedRunePage`.
+    CaptureRunePage,
+    /// Lock the first available pick on my current pick turn right away.
+    PickNow,
+    /// Lock my ban right away instead of hovering it first.
+    BanNow,
+    /// Accept (`true`) or decline a lobby invitation by its id.
+    AnswerInvitation(String, bool),
+    /// Create a practice lobby and start it, as a Practice Tool game when `true` or as a plain
+    /// custom game otherwise.
+    StartPracticeTool(bool),
+    /// The kill switch was pressed, stop all automation without waiting for the new settings.
+    KillSwitch,
+    /// Set my primary and secondary position in the lobby, as the client names them (`TOP`,
+    /// `FILL`, ...).
+    SetPositionPreferences(String, String),
+}
+
+/// Sent from the automation worker to the UI, which drains them every frame.
+enum Event {
+    Status(String),
+    AssignedRole(Option<String>),
+    /// The raw gameflow phase as the client names it (`None`, `Lobby`, `ChampSelect`, ...), or
+    /// `None` while no client is connected. Sent whenever it changes.
+    Phase(Option<String>),
+    ChampSelectSession(serde_json::Value),
+    PickMode(Option<PickMode>),
+    /// An entry for the "Recent actions" list.
+    Action(String),
+    ClearActions,
+    /// The puuid and name of the logged in summoner.
+    Summoner(Option<(String, String)>),
+    /// The worker changed the spell selection, e.g. to add Smite when jungling.
+    Spells(Option<String>, Option<String>),
+    CapturedRunePage(String),
+    /// A configured champion isn't known to the client, `champions.json` is likely stale.
+    ChampionDataOutdated,
+    FeatureState(Feature, FeatureState),
+    /// The pending lobby invitations, sent whenever they change.
+    Invitations(Vec<Invitation>),
+    /// Round trip of the last gameflow request to the client.
+    Latency(std::time::Duration),
+    /// The client's region (e.g. `EUW`), sent once the summoner is known.
+    Region(String),
+    /// My primary and secondary position in the lobby, sent whenever they change.
+    PositionPreferences(String, String),
+    /// The icon of a champion by id, as PNG bytes from the client.
+    ChampionIcon(u32, Vec<u8>),
+    /// The owned and free rotation champions of this account, sent once connected.
+    ChampionInventory(HashMap<u32, Availability>),
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 /// The `Champion` struct is a data structure used for (de)serialization of the `champsions.json` file.
 ///
@@ -53,9 +279,35 @@ pub struct GUI {
 /// * `id`: The `id` property is of type `u32`, which stands for "unsigned 32-bit integer". It is used
 /// to uniquely identify each instance of the `Champion` struct.
 /// * `name`: The `name` property is a string that represents the name of a champion.
+/// * `alias`: The Data Dragon key of the champion (e.g. `MonkeyKing` for Wukong), empty for
+/// champion data saved before it was recorded.
 struct Champion {
     id: u32,
     name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    alias: String,
+}
+
+impl Champion {
+    /// Whether the name or alias equals `cleaned`, a name passed through `clean_champion_name`.
+    fn matches(&self, cleaned: &str) -> bool {
+        clean_champion_name(&self.name) == cleaned
+            || (!self.alias.is_empty() && clean_champion_name(&self.alias) == cleaned)
+    }
+
+    fn matches_prefix(&self, cleaned: &str) -> bool {
+        clean_champion_name(&self.name).starts_with(cleaned)
+            || (!self.alias.is_empty() && clean_champion_name(&self.alias).starts_with(cleaned))
+    }
+
+    /// The name, followed by the alias when it's wanted and differs from the name.
+    fn label(&self, show_alias: bool) -> String {
+        if show_alias && !self.alias.is_empty() && self.alias != self.name {
+            format!("{} ({})", self.name, self.alias)
+        } else {
+            self.name.clone()
+        }
+    }
 }
 
 #[allow(non_snake_case)]
@@ -73,12 +325,87 @@ struct Champion {
 /// is currently in progress or not.
 /// * `r#type`: The property "r#type" is a string that represents the type of action response data. The
 /// "r#" prefix is used to escape the reserved keyword "type" in Rust.
+/// * `championId`: The champion hovered or locked in by the action, 0 if none yet.
+/// * `isAllyAction`: Whether the actor is on my team.
 struct ActionResponseData {
     actorCellId: i32,
     completed: bool,
     id: i32,
     isInProgress: bool,
     r#type: String,
+    #[serde(default)]
+    championId: u32,
+    #[serde(default)]
+    isAllyAction: bool,
+}
+
+/// Parses the `actions` of a champion select session. Each inner list is one turn, the actions in
+/// it happen at the same time (e.g. all bans of a team).
+fn turn_order(session: &serde_json::Value) -> Vec<Vec<ActionResponseData>> {
+    serde_json::from_value(session["actions"].clone()).unwrap_or_default()
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug, Clone)]
+/// An enemy cell of the champion select session. The client only fills in what the enemy team
+/// shows, so every field may be missing.
+struct TheirTeamData {
+    #[serde(default)]
+    championId: u32,
+    #[serde(default)]
+    championPickIntent: u32,
+}
+
+/// The champion each enemy has locked in, `None` for the ones still picking. Hovers are left out.
+fn enemy_champions(session: &serde_json::Value) -> Vec<Option<u32>> {
+    let their_team: Vec<TheirTeamData> =
+        serde_json::from_value(session["theirTeam"].clone()).unwrap_or_default();
+    their_team
+        .iter()
+        .map(|cell| Some(cell.championId).filter(|id| *id != 0))
+        .collect()
+}
+
+/// Returns the first champion of the watchlist an enemy is hovering or has picked that can still be
+/// banned.
+fn counter_ban(
+    session: &serde_json::Value,
+    watchlist: &[(u32, String)],
+    is_available: impl Fn(u32) -> bool,
+) -> Option<(u32, String)> {
+    let their_team: Vec<TheirTeamData> =
+        serde_json::from_value(session["theirTeam"].clone()).unwrap_or_default();
+    watchlist
+        .iter()
+        .find(|(id, _)| {
+            *id != 0
+                && is_available(*id)
+                && their_team
+                    .iter()
+                    .any(|cell| cell.championPickIntent == *id || cell.championId == *id)
+        })
+        .cloned()
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+/// The `timer` of a champion select session. It can be missing while the session transitions
+/// between phases.
+struct Timer {
+    phase: String,
+    adjustedTimeLeftInPhase: i64,
+}
+
+impl Timer {
+    /// Parses the timer of a champion select session, `None` when it's missing or malformed.
+    fn from_session(session: &serde_json::Value) -> Option<Timer> {
+        serde_json::from_value(session.get("timer")?.clone()).ok()
+    }
+
+    /// Whether picks and bans can be sent, not during planning or once the picks are final.
+    fn allows_actions(&self) -> bool {
+        self.phase == "BAN_PICK"
+    }
 }
 
 #[allow(non_snake_case)]
@@ -86,12 +413,485 @@ struct ActionResponseData {
 struct MyTeamData {
     cellId: u32,
     assignedPosition: String,
-    spell1Id: u32,
-    spell2Id: u32,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+/// What the pick automation does when none of the configured picks can be picked.
+enum PickFallback {
+    #[default]
+    Nothing,
+    RandomOwned,
+    SecondaryPool,
+}
+
+impl PickFallback {
+    const ALL: [PickFallback; 3] = [
+        PickFallback::Nothing,
+        PickFallback::RandomOwned,
+        PickFallback::SecondaryPool,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            PickFallback::Nothing => "Do nothing",
+            PickFallback::RandomOwned => "Lock a random owned champion",
+            PickFallback::SecondaryPool => "Hover from secondary pool",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How the configured picks are used, detected from the number of pick turns I get in the current
+/// champion select.
+enum PickMode {
+    /// One pick turn: the picks are a priority list, the first available one gets locked.
+    Priority,
+    /// Several pick turns: each slot is locked on its own turn.
+    Separate,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+/// How incoming champion trade requests are answered during champion select.
+enum TradeBehavior {
+    #[default]
+    Off,
+    AcceptAll,
+    KeepPick,
+}
+
+impl TradeBehavior {
+    const ALL: [TradeBehavior; 3] = [
+        TradeBehavior::Off,
+        TradeBehavior::AcceptAll,
+        TradeBehavior::KeepPick,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            TradeBehavior::Off => "Answer manually",
+            TradeBehavior::AcceptAll => "Accept all trades",
+            TradeBehavior::KeepPick => "Only accept trades for my picks",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+/// How incoming position swap requests are answered during champion select.
+enum SwapBehavior {
+    #[default]
+    Off,
+    AcceptAll,
+    DeclineAll,
+}
+
+impl SwapBehavior {
+    const ALL: [SwapBehavior; 3] = [
+        SwapBehavior::Off,
+        SwapBehavior::AcceptAll,
+        SwapBehavior::DeclineAll,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SwapBehavior::Off => "Answer manually",
+            SwapBehavior::AcceptAll => "Accept all swaps",
+            SwapBehavior::DeclineAll => "Decline all swaps",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An automation listed in the status panel, also its index into the list of states.
+enum Feature {
+    AutoAccept,
+    PickBan,
+    Spells,
+    Runes,
+}
+
+impl Feature {
+    const ALL: [Feature; 4] = [
+        Feature::AutoAccept,
+        Feature::PickBan,
+        Feature::Spells,
+        Feature::Runes,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Feature::AutoAccept => "Auto Accept",
+            Feature::PickBan => "Auto Pick/Ban",
+            Feature::Spells => "Spell Selection",
+            Feature::Runes => "Rune Swap",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// What an automation did since the last match was queued.
+enum FeatureState {
+    /// Turned off.
+    #[default]
+    Idle,
+    /// Turned on and waiting for its turn.
+    Armed,
+    /// Did its action in the current session.
+    Fired,
+    /// A request to the client failed.
+    Error,
+}
+
+impl FeatureState {
+    fn label(&self) -> &'static str {
+        match self {
+            FeatureState::Idle => "Idle",
+            FeatureState::Armed => "Armed",
+            FeatureState::Fired => "Fired",
+            FeatureState::Error => "Error",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+/// The `Settings` struct is the persisted configuration stored in `utils/settings.json`.
+///
+/// Missing fields fall back to their defaults and files written by older versions are upgraded
+/// by `Settings::migrate` before being parsed, so older files keep loading.
+struct Settings {
+    version: u32,
+    pick_ban_selection: bool,
+    auto_accept: bool,
+    spell_selection: bool,
+    /// Positions (`top`, `jungle`, ...) spells are left alone for.
+    spell_skip_roles: Vec<String>,
+    match_log: bool,
+    in_game_guard: bool,
+    champion_picks: Vec<(u32, String)>,
+    /// My bans in order. Modes with several bans use the next one for each ban turn.
+    ban_picks: Vec<(u32, String)>,
+    pick_fallback: PickFallback,
+    secondary_picks: Vec<(u32, String)>,
+    trade_behavior: TradeBehavior,
+    swap_behavior: SwapBehavior,
+    hover_ban: bool,
+    action_retries: u32,
+    max_picks: usize,
+    protect_picks: bool,
+    autofill_picks_only: bool,
+    playable_picks: bool,
+    live_game_stats: bool,
+    counter_ban: bool,
+    counter_bans: Vec<(u32, String)>,
+    decoy_hover: bool,
+    decoy_ban: Option<(u32, String)>,
+    skip_ban: bool,
+    winrate_picks: bool,
+    stats_url: String,
+    quiet_hours: bool,
+    quiet_hours_start: String,
+    quiet_hours_end: String,
+    queue_automation: HashMap<u32, QueueAutomation>,
+    ban_rules: Vec<BanRule>,
+    champion_notes: HashMap<u32, String>,
+    locale: String,
+    always_on_top: bool,
+    minimize_on_close: bool,
+    remember_window_position: bool,
+    battery_saver: bool,
+    kill_switch_key: String,
+    /// Start the practice game as a Practice Tool game with its cheats, or as a plain custom
+    /// game on Summoner's Rift.
+    practice_tool_cheats: bool,
+    rune_page_selection: bool,
+    rune_pages: HashMap<u32, RunePage>,
+    per_account_picks: bool,
+    show_champion_alias: bool,
+    account_profiles: HashMap<String, AccountProfile>,
+    spell1: Option<String>,
+    spell2: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            pick_ban_selection: false,
+            auto_accept: false,
+            spell_selection: false,
+            spell_skip_roles: Vec::new(),
+            match_log: false,
+            in_game_guard: true,
+            champion_picks: Vec::new(),
+            ban_picks: Vec::new(),
+            pick_fallback: PickFallback::Nothing,
+            secondary_picks: Vec::new(),
+            trade_behavior: TradeBehavior::Off,
+            swap_behavior: SwapBehavior::Off,
+            hover_ban: false,
+            action_retries: 2,
+            max_picks: 5,
+            protect_picks: true,
+            autofill_picks_only: false,
+            playable_picks: false,
+            live_game_stats: false,
+            counter_ban: false,
+            counter_bans: Vec::new(),
+            decoy_hover: false,
+            decoy_ban: None,
+            skip_ban: false,
+            winrate_picks: false,
+            stats_url: String::new(),
+            quiet_hours: false,
+            quiet_hours_start: "23:00".to_owned(),
+            quiet_hours_end: "08:00".to_owned(),
+            queue_automation: HashMap::new(),
+            ban_rules: Vec::new(),
+            champion_notes: HashMap::new(),
+            locale: "en_US".to_owned(),
+            always_on_top: false,
+            minimize_on_close: false,
+            remember_window_position: false,
+            battery_saver: false,
+            kill_switch_key: "K".to_owned(),
+            practice_tool_cheats: true,
+            rune_page_selection: false,
+            rune_pages: HashMap::new(),
+            per_account_picks: false,
+            show_champion_alias: false,
+            account_profiles: HashMap::new(),
+            spell1: None,
+            spell2: None,
+        }
+    }
+}
+
+impl Settings {
+    fn load() -> Self {
+        std::fs::read_to_string(utils_path("settings.json"))
+            .ok()
+            .and_then(|json| Self::from_json(&json))
+            .unwrap_or_default()
+    }
+
+    fn from_json(json: &str) -> Option<Self> {
+        let mut settings: serde_json::Value = serde_json::from_str(json).ok()?;
+        Self::migrate(&mut settings);
+        serde_json::from_value(settings).ok()
+    }
+
+    /// Upgrades a settings file written by an older version to the current shape, one version at
+    /// a time. Renamed or reshaped fields get converted here, new fields are filled with their
+    /// defaults when deserializing.
+    fn migrate(settings: &mut serde_json::Value) {
+        let Some(fields) = settings.as_object_mut() else {
+            return;
+        };
+
+        // version 1 files predate the `version` field
+        let mut version = fields
+            .get("version")
+            .and_then(|version| version.as_u64())
+            .unwrap_or(1) as u32;
+        while version < SETTINGS_VERSION {
+            match version {
+                // version 2 only introduced the `version` field itself
+                1 => {}
+                // a skipped ban used to be stored as an id-0 ban with an empty name
+                2 => {
+                    if fields.get("ban_picks") == Some(&serde_json::json!([0, ""])) {
+                        fields.insert("ban_picks".to_owned(), serde_json::Value::Null);
+                        fields.insert("skip_ban".to_owned(), true.into());
+                    }
+                }
+                // the ban became a list, also in the per-account profiles
+                3 => {
+                    Self::ban_to_list(fields);
+                    if let Some(profiles) = fields
+                        .get_mut("account_profiles")
+                        .and_then(|profiles| profiles.as_object_mut())
+                    {
+                        for profile in profiles.values_mut().filter_map(|p| p.as_object_mut()) {
+                            Self::ban_to_list(profile);
+                        }
+                    }
+                }
+                _ => break,
+            }
+            version += 1;
+        }
+        fields.insert("version".to_owned(), version.into());
+    }
+
+    /// Turns a single `ban_picks` entry into a one-entry list, no ban into an empty one.
+    fn ban_to_list(fields: &mut serde_json::Map<String, serde_json::Value>) {
+        let bans = match fields.remove("ban_picks") {
+            Some(serde_json::Value::Null) | None => serde_json::json!([]),
+            Some(ban) => serde_json::json!([ban]),
+        };
+        fields.insert("ban_picks".to_owned(), bans);
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        let settings_path = utils_path("settings.json");
+        if let Some(utils_dir) = settings_path.parent() {
+            std::fs::create_dir_all(utils_dir)?;
+        }
+        std::fs::write(settings_path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+/// Window state restored on the next start, kept in `utils/ui_state.json` apart from the settings
+/// so it changing doesn't rewrite them or reach the worker.
+struct UiState {
+    active_tab: usize,
+    window_position: Option<(f32, f32)>,
+    ui_scale: f32,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        Self {
+            active_tab: 0,
+            window_position: None,
+            ui_scale: 1.0,
+        }
+    }
+}
+
+impl UiState {
+    /// Falls back to the settings file, which held these fields before they moved here.
+    fn load() -> Self {
+        ["ui_state.json", "settings.json"]
+            .iter()
+            .find_map(|file| std::fs::read_to_string(utils_path(file)).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        let ui_state_path = utils_path("ui_state.json");
+        if let Some(utils_dir) = ui_state_path.parent() {
+            std::fs::create_dir_all(utils_dir)?;
+        }
+        std::fs::write(ui_state_path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+/// A rune page in the format the League Client uses for `/lol-perks/v1/pages`, which is also what
+/// most build sites export.
+struct RunePage {
+    #[serde(default = "default_rune_page_name")]
+    name: String,
+    primaryStyleId: u32,
+    subStyleId: u32,
+    selectedPerkIds: Vec<u32>,
+}
+
+fn default_rune_page_name() -> String {
+    "Circuit Watcher".to_owned()
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+/// Which automations run in one queue. A queue listed here ignores the global toggles.
+struct QueueAutomation {
+    auto_accept: bool,
+    pick_ban: bool,
+    spells: bool,
+}
+
+/// The automation set for `queue_id`, if any. The kill switch keeps a queue's own toggles off
+/// too, only turning automation back on in the settings releases it.
+fn queue_override(
+    settings: &Settings,
+    queue_id: Option<u32>,
+    kill_switch: bool,
+) -> Option<QueueAutomation> {
+    queue_id
+        .filter(|_| !kill_switch)
+        .and_then(|queue_id| settings.queue_automation.get(&queue_id))
+        .copied()
+}
+
+/// Queues offered when adding per-queue automation, by queue id.
+const QUEUES: [(u32, &str); 7] = [
+    (400, "Normal Draft"),
+    (420, "Ranked Solo/Duo"),
+    (430, "Normal Blind"),
+    (440, "Ranked Flex"),
+    (450, "ARAM"),
+    (490, "Quickplay"),
+    (1700, "Arena"),
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+/// A ban used instead of the usual one when I'm assigned `position` in `queue`, `None` matching
+/// any position or queue.
+struct BanRule {
+    position: Option<String>,
+    queue: Option<u32>,
+    champion: (u32, String),
+}
+
+/// The champion of the first rule matching the assigned position and queue.
+fn rule_ban(
+    rules: &[BanRule],
+    position: Option<&str>,
+    queue: Option<u32>,
+) -> Option<(u32, String)> {
+    rules
+        .iter()
+        .find(|rule| {
+            rule.position
+                .as_deref()
+                .map_or(true, |rule_position| Some(rule_position) == position)
+                && rule
+                    .queue
+                    .map_or(true, |rule_queue| Some(rule_queue) == queue)
+        })
+        .map(|rule| rule.champion.clone())
+}
+
+fn queue_name(queue_id: u32) -> String {
+    QUEUES.iter().find(|(id, _)| *id == queue_id).map_or_else(
+        || format!("Queue {}", queue_id),
+        |(_, name)| name.to_string(),
+    )
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+/// The pick pool remembered for one League account while per-account picks are on.
+struct AccountProfile {
+    summoner_name: String,
+    champion_picks: Vec<(u32, String)>,
+    ban_picks: Vec<(u32, String)>,
+    secondary_picks: Vec<(u32, String)>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+/// A lobby invitation from `/lol-lobby/v2/received-invitations`.
+struct Invitation {
+    invitationId: String,
+    #[serde(default)]
+    fromSummonerName: String,
+    #[serde(default)]
+    state: String,
 }
 
 #[derive(Deserialize, Debug)]
 struct Release {
+    #[serde(default)]
     assets: Vec<Asset>,
 }
 
@@ -101,89 +901,611 @@ struct Asset {
     browser_download_url: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 struct SummonerSpell {
+    id: String,
     key: u32,
     name: String,
 }
 
+#[derive(Deserialize, Debug)]
+struct DataDragonSummoners {
+    data: HashMap<String, DataDragonSpell>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DataDragonSpell {
+    id: String,
+    key: String,
+    name: String,
+    image: DataDragonImage,
+}
+
+#[derive(Deserialize, Debug)]
+struct DataDragonImage {
+    full: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct DataDragonChampions {
+    data: HashMap<String, DataDragonChampion>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DataDragonChampion {
+    id: String,
+    key: String,
+    name: String,
+}
+
+/// Data Dragon locales offered for champion names, matching the languages of the League client.
+const LOCALES: [&str; 14] = [
+    "en_US", "de_DE", "es_ES", "fr_FR", "it_IT", "pl_PL", "pt_BR", "ru_RU", "tr_TR", "ja_JP",
+    "ko_KR", "zh_CN", "zh_TW", "vi_VN",
+];
+
+/// Holds encoded image bytes and decodes them into `RetainedImage`s on first display, keeping at
+/// most `capacity` decoded images around by evicting the least recently used ones. Pinned images
+/// are decoded up front and never evicted.
+struct ImageCache {
+    raw: HashMap<String, Vec<u8>>,
+    decoded: HashMap<String, RetainedImage>,
+    pinned: Vec<String>,
+    recently_used: VecDeque<String>,
+    capacity: usize,
+}
+
+impl ImageCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            raw: HashMap::new(),
+            decoded: HashMap::new(),
+            pinned: Vec::new(),
+            recently_used: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    fn insert_pinned(&mut self, name: String, image: RetainedImage) {
+        self.pinned.push(name.clone());
+        self.decoded.insert(name, image);
+    }
+
+    fn insert_raw(&mut self, name: String, bytes: Vec<u8>) {
+        if !self.pinned.contains(&name) {
+            self.decoded.remove(&name);
+            self.raw.insert(name, bytes);
+        }
+    }
+
+    /// Returns the names of every image in the cache, decoded or not, in alphabetical order.
+    fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.pinned.iter().chain(self.raw.keys()).cloned().collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn texture_id(&mut self, name: &str, ctx: &egui::Context) -> Option<egui::TextureId> {
+        if !self.decoded.contains_key(name) {
+            let image = RetainedImage::from_image_bytes(name, self.raw.get(name)?).ok()?;
+            self.decoded.insert(name.to_owned(), image);
+        }
+
+        if !self.pinned.iter().any(|pinned| pinned == name) {
+            self.recently_used.retain(|used| used != name);
+            self.recently_used.push_back(name.to_owned());
+            while self.recently_used.len() > self.capacity {
+                if let Some(evicted) = self.recently_used.pop_front() {
+                    self.decoded.remove(&evicted);
+                }
+            }
+        }
+
+        self.decoded.get(name).map(|image| image.texture_id(ctx))
+    }
+}
+
 impl GUI {
-    fn new(/*cc: &eframe::CreationContext<'_>*/) -> Self {
+    fn new(
+        /*cc: &eframe::CreationContext<'_>,*/
+        commands: mpsc::UnboundedSender<Command>,
+        events: mpsc::UnboundedReceiver<Event>,
+    ) -> Self {
         // Customize egui here with cc.egui_ctx.set_fonts and cc.egui_ctx.set_visuals.
         // Restore app state using cc.storage (requires the "persistence" feature).
         // Use the cc.gl (a glow::Context) to create graphics shaders and buffers that you can use
         // for e.g. egui::PaintCallback.
 
+        let settings = Settings::load();
+        let ui_state = UiState::load();
+
         // Initialize checkbox states
-        let pick_ban_selection = Arc::new(AtomicBool::new(false));
-        let rune_page_selection = Arc::new(AtomicBool::new(false));
-        let auto_accept = Arc::new(AtomicBool::new(false));
-        let summoner_spell_selection = Arc::new(AtomicBool::new(false));
+        let pick_ban_selection = Arc::new(AtomicBool::new(settings.pick_ban_selection));
+        let rune_page_selection = Arc::new(AtomicBool::new(settings.rune_page_selection));
+        let auto_accept = Arc::new(AtomicBool::new(settings.auto_accept));
+        let summoner_spell_selection = Arc::new(AtomicBool::new(settings.spell_selection));
         let connection_status = Arc::new(Mutex::new(None));
-        let json_data =
-            std::fs::read_to_string("./utils/champions.json").expect("Failed to read file");
-        let champions: Vec<Champion> =
-            serde_json::from_str(&json_data).expect("Failed to parse JSON");
-
-        let mut images: HashMap<String, RetainedImage> = HashMap::new();
-
-        let barrier_img = image_loader("Barrier", include_bytes!("../utils/images/barrier.png"));
-        let exhaust_img = image_loader("Exhaust", include_bytes!("../utils/images/exhaust.png"));
-        let flash_img = image_loader("Flash", include_bytes!("../utils/images/flash.png"));
-        let ghost_img = image_loader("Ghost", include_bytes!("../utils/images/ghost.png"));
-        let heal_img = image_loader("Heal", include_bytes!("../utils/images/heal.png"));
-        let ignite_img = image_loader("Ignite", include_bytes!("../utils/images/ignite.png"));
-        let smite_img = image_loader("Smite", include_bytes!("../utils/images/smite.png"));
-        let teleport_img = image_loader("Teleport", include_bytes!("../utils/images/teleport.png"));
-        let no_icon_img = image_loader("no_icon", include_bytes!("../utils/images/no_icon.png")).1;
-
-        images.insert(barrier_img.0, barrier_img.1);
-        images.insert(exhaust_img.0, exhaust_img.1);
-        images.insert(flash_img.0, flash_img.1);
-        images.insert(ghost_img.0, ghost_img.1);
-        images.insert(heal_img.0, heal_img.1);
-        images.insert(ignite_img.0, ignite_img.1);
-        images.insert(smite_img.0, smite_img.1);
-        images.insert(teleport_img.0, teleport_img.1);
+        // Every bundled asset is checked here, a broken one is reported in a window instead of
+        // crashing before the window appears.
+        let mut startup_problems = Vec::new();
+        let champions: Vec<Champion> = validate_champions(load_utils_json(
+            "champions.json",
+            include_str!("../utils/champions.json"),
+            &mut startup_problems,
+        ));
+        let summoner_spells: Vec<SummonerSpell> = load_utils_json(
+            "summoner_spells.json",
+            include_str!("../utils/summoner_spells.json"),
+            &mut startup_problems,
+        );
+        if reqwest::Certificate::from_pem(RIOT_CERT).is_err() {
+            startup_problems.push(
+                "riotgames.pem couldn't be loaded, the League Client can't be reached.".to_owned(),
+            );
+        }
+
+        let mut images = ImageCache::new(IMAGE_CACHE_CAPACITY);
+        for (name, bytes) in [
+            (
+                "Barrier",
+                include_bytes!("../utils/images/barrier.png").as_slice(),
+            ),
+            (
+                "Exhaust",
+                include_bytes!("../utils/images/exhaust.png").as_slice(),
+            ),
+            (
+                "Flash",
+                include_bytes!("../utils/images/flash.png").as_slice(),
+            ),
+            (
+                "Ghost",
+                include_bytes!("../utils/images/ghost.png").as_slice(),
+            ),
+            (
+                "Heal",
+                include_bytes!("../utils/images/heal.png").as_slice(),
+            ),
+            (
+                "Ignite",
+                include_bytes!("../utils/images/ignite.png").as_slice(),
+            ),
+            (
+                "Smite",
+                include_bytes!("../utils/images/smite.png").as_slice(),
+            ),
+            (
+                "Teleport",
+                include_bytes!("../utils/images/teleport.png").as_slice(),
+            ),
+        ] {
+            if let Some(image) = image_loader(name, bytes, &mut startup_problems) {
+                images.insert_pinned(name.to_owned(), image);
+            }
+        }
+        let no_icon_img = image_loader(
+            "no_icon",
+            include_bytes!("../utils/images/no_icon.png"),
+            &mut startup_problems,
+        )
+        .unwrap_or_else(|| {
+            RetainedImage::from_color_image(
+                "no_icon",
+                egui::ColorImage::new([1, 1], egui::Color32::TRANSPARENT),
+            )
+        });
 
         Self {
             pick_ban_selection,
             rune_page_selection,
+            rune_pages: settings.rune_pages.clone(),
+            rune_champion_text: String::new(),
+            rune_page_text: String::new(),
+            rune_feedback: None,
             auto_accept,
+            match_log: Arc::new(AtomicBool::new(settings.match_log)),
+            in_game_guard: Arc::new(AtomicBool::new(settings.in_game_guard)),
             pick_text: String::new().to_owned(),
             ban_text: String::new().to_owned(),
-            champion_picks: Arc::new(Mutex::new(Vec::new())),
-            ban_picks: Arc::new(Mutex::new(None)),
+            champion_picks: settings.champion_picks.clone(),
+            ban_picks: settings.ban_picks.clone(),
+            pick_fallback: settings.pick_fallback,
+            secondary_picks: settings.secondary_picks.clone(),
+            secondary_text: String::new(),
+            counter_ban_text: watchlist_text(&settings.counter_bans),
+            counter_ban_error: None,
+            decoy_ban_text: String::new(),
+            decoy_ban_error: None,
+            trade_behavior: settings.trade_behavior,
+            swap_behavior: settings.swap_behavior,
+            hover_ban: settings.hover_ban,
+            action_retries: settings.action_retries,
+            max_picks: settings.max_picks,
+            protect_picks: settings.protect_picks,
+            autofill_picks_only: settings.autofill_picks_only,
+            playable_picks: settings.playable_picks,
+            live_game_stats: settings.live_game_stats,
+            counter_ban: settings.counter_ban,
+            counter_bans: settings.counter_bans.clone(),
+            decoy_hover: settings.decoy_hover,
+            decoy_ban: settings.decoy_ban.clone(),
+            skip_ban: settings.skip_ban,
+            winrate_picks: settings.winrate_picks,
+            stats_url: settings.stats_url.clone(),
+            quiet_hours: settings.quiet_hours,
+            quiet_hours_start: settings.quiet_hours_start.clone(),
+            quiet_hours_end: settings.quiet_hours_end.clone(),
+            queue_automation: settings.queue_automation.clone(),
+            ban_rules: settings.ban_rules.clone(),
+            ban_rule_position: None,
+            ban_rule_queue: None,
+            ban_rule_text: String::new(),
+            ban_rule_error: None,
+            champion_notes: settings.champion_notes.clone(),
+            pick_mode: None,
+            feature_states: [FeatureState::Idle; 4],
+            invitations: Vec::new(),
+            latency: None,
+            region: None,
+            lobby_positions: None,
+            champion_inventory: None,
+            startup_problems,
             clear_label_timer: None,
+            cleared_picks: None,
             pick_not_found_label_timer: None,
+            entry_focus: false,
             ban_not_found_label_timer: None,
             connection_status,
             champions,
+            champion_locale: Arc::new(Mutex::new(settings.locale.clone())),
+            downloaded_champions: Arc::new(Mutex::new(None)),
+            refresh_champions: Arc::new(AtomicBool::new(false)),
+            champion_data_outdated: false,
+            sent_summoner_spells: summoner_spells.clone(),
+            summoner_spells: Arc::new(Mutex::new(summoner_spells)),
+            downloaded_spell_icons: Arc::new(Mutex::new(Vec::new())),
             text: String::new().to_owned(),
-            gameflow_status: Arc::new(Mutex::new(String::new())),
+            gameflow_status: String::new(),
             update_status: Arc::new(Mutex::new(String::new())),
-            current_version: Arc::new(Mutex::new(String::new())),
+            current_version: Arc::new(Mutex::new(env!("CARGO_PKG_VERSION").to_owned())),
+            release_notes: Arc::new(Mutex::new(None)),
             update: Arc::new(AtomicBool::new(false)),
             update_button_clicked: false,
-            asset_name: Arc::new(Mutex::new("./utils/champions.json".to_owned())), // champions.json will always be in the folder and has a really small size.
+            quit_requested: false,
+            minimize_requested: false,
+            restart_error: None,
+            confirm_reset: false,
+            diagnostics_status: None,
+            spell_editor_open: false,
+            logs_open: false,
+            spell_editor_status: None,
+            plan_window_open: false,
+            owned_window_open: false,
+            owned_selection: HashSet::new(),
+            plan_rect: None,
+            plan_export_requested: false,
+            plan_export_status: None,
+            pixels_per_point: 1.0,
+            reset_requested: false,
+            asset_name: Arc::new(Mutex::new(
+                utils_path("champions.json").to_string_lossy().into_owned(),
+            )), // champions.json will always be in the folder and has a really small size.
             images,
-            selected_image1: Arc::new(Mutex::new(None)),
-            selected_image2: Arc::new(Mutex::new(None)),
+            selected_image1: settings.spell1.clone(),
+            selected_image2: settings.spell2.clone(),
             no_icon_img,
+            champion_icons: ImageCache::new(IMAGE_CACHE_CAPACITY),
             spell_selection: summoner_spell_selection,
-            assigned_role: Arc::new(Mutex::new(None)),
-            active_tab: 0,
+            spell_skip_roles: settings.spell_skip_roles.clone(),
+            assigned_role: None,
+            phase: None,
+            champ_select_session: None,
+            action_log: VecDeque::new(),
+            debug_mode: false,
+            always_on_top: settings.always_on_top,
+            minimize_on_close: settings.minimize_on_close,
+            remember_window_position: settings.remember_window_position,
+            ui_scale: ui_state.ui_scale,
+            battery_saver: settings.battery_saver,
+            kill_switch_key: settings.kill_switch_key.clone(),
+            kill_switch_code: Arc::new(AtomicU32::new(
+                hotkey_code(&settings.kill_switch_key).unwrap_or(0),
+            )),
+            practice_tool_cheats: settings.practice_tool_cheats,
+            automation_killed: Arc::new(AtomicBool::new(false)),
+            window_position: ui_state.window_position,
+            active_account: None,
+            per_account_picks: settings.per_account_picks,
+            show_champion_alias: settings.show_champion_alias,
+            account_profiles: settings.account_profiles.clone(),
+            // an unknown tab from a newer version falls back to the first one
+            active_tab: if ui_state.active_tab < TABS.len() {
+                ui_state.active_tab
+            } else {
+                0
+            },
+            saved_ui_state: ui_state,
+            saved_settings: settings,
+            commands,
+            events,
+        }
+    }
+
+    /// Returns the pick pool of the logged in account, keyed by its puuid, when picks are kept per
+    /// account.
+    fn current_profile(&self) -> Option<(String, AccountProfile)> {
+        if !self.per_account_picks {
+            return None;
+        }
+        let (puuid, summoner_name) = self.active_account.clone()?;
+
+        Some((
+            puuid,
+            AccountProfile {
+                summoner_name,
+                champion_picks: self.champion_picks.clone(),
+                ban_picks: self.ban_picks.clone(),
+                secondary_picks: self.secondary_picks.clone(),
+            },
+        ))
+    }
+
+    /// Remembers the pick pool of the account being logged out of and loads the one of the account
+    /// being logged into, if it has one yet.
+    fn switch_account(&mut self, account: Option<(String, String)>) {
+        if let Some((puuid, profile)) = self.current_profile() {
+            self.account_profiles.insert(puuid, profile);
+        }
+
+        if self.per_account_picks {
+            if let Some(profile) = account
+                .as_ref()
+                .and_then(|(puuid, _)| self.account_profiles.get(puuid))
+            {
+                self.champion_picks = profile.champion_picks.clone();
+                self.ban_picks = profile.ban_picks.clone();
+                self.secondary_picks = profile.secondary_picks.clone();
+            }
+        }
+
+        self.active_account = account;
+    }
+
+    /// Replaces the current configuration with `settings`, it's persisted and sent to the worker
+    /// on the next frame like any other change.
+    fn load_settings(&mut self, settings: Settings) {
+        self.pick_ban_selection
+            .store(settings.pick_ban_selection, Ordering::SeqCst);
+        self.auto_accept
+            .store(settings.auto_accept, Ordering::SeqCst);
+        self.spell_selection
+            .store(settings.spell_selection, Ordering::SeqCst);
+        self.spell_skip_roles = settings.spell_skip_roles;
+        self.match_log.store(settings.match_log, Ordering::SeqCst);
+        self.in_game_guard
+            .store(settings.in_game_guard, Ordering::SeqCst);
+        self.rune_page_selection
+            .store(settings.rune_page_selection, Ordering::SeqCst);
+        self.champion_picks = settings.champion_picks;
+        self.ban_picks = settings.ban_picks;
+        self.pick_fallback = settings.pick_fallback;
+        self.secondary_picks = settings.secondary_picks;
+        self.trade_behavior = settings.trade_behavior;
+        self.swap_behavior = settings.swap_behavior;
+        self.hover_ban = settings.hover_ban;
+        self.action_retries = settings.action_retries;
+        self.max_picks = settings.max_picks;
+        self.protect_picks = settings.protect_picks;
+        self.autofill_picks_only = settings.autofill_picks_only;
+        self.playable_picks = settings.playable_picks;
+        self.live_game_stats = settings.live_game_stats;
+        self.counter_ban = settings.counter_ban;
+        self.counter_ban_text = watchlist_text(&settings.counter_bans);
+        self.counter_bans = settings.counter_bans;
+        self.decoy_hover = settings.decoy_hover;
+        self.decoy_ban = settings.decoy_ban;
+        self.skip_ban = settings.skip_ban;
+        self.winrate_picks = settings.winrate_picks;
+        self.stats_url = settings.stats_url;
+        self.quiet_hours = settings.quiet_hours;
+        self.quiet_hours_start = settings.quiet_hours_start;
+        self.quiet_hours_end = settings.quiet_hours_end;
+        self.queue_automation = settings.queue_automation;
+        self.ban_rules = settings.ban_rules;
+        self.champion_notes = settings.champion_notes;
+        *self.champion_locale.lock().unwrap() = settings.locale;
+        self.rune_pages = settings.rune_pages;
+        self.selected_image1 = settings.spell1;
+        self.selected_image2 = settings.spell2;
+        self.always_on_top = settings.always_on_top;
+        self.minimize_on_close = settings.minimize_on_close;
+        self.remember_window_position = settings.remember_window_position;
+        self.battery_saver = settings.battery_saver;
+        self.kill_switch_key = settings.kill_switch_key;
+        self.practice_tool_cheats = settings.practice_tool_cheats;
+        self.per_account_picks = settings.per_account_picks;
+        self.show_champion_alias = settings.show_champion_alias;
+        self.account_profiles = settings.account_profiles;
+    }
+
+    /// Collects the window state kept apart from the settings, see [`UiState`].
+    fn ui_state(&self) -> UiState {
+        UiState {
+            active_tab: self.active_tab,
+            window_position: self.window_position,
+            ui_scale: self.ui_scale,
+        }
+    }
+
+    /// Collects the current configuration so it can be compared against and written to disk.
+    fn settings(&self) -> Settings {
+        let mut account_profiles = self.account_profiles.clone();
+        if let Some((puuid, profile)) = self.current_profile() {
+            account_profiles.insert(puuid, profile);
+        }
+
+        Settings {
+            version: SETTINGS_VERSION,
+            pick_ban_selection: self.pick_ban_selection.load(Ordering::SeqCst),
+            auto_accept: self.auto_accept.load(Ordering::SeqCst),
+            spell_selection: self.spell_selection.load(Ordering::SeqCst),
+            spell_skip_roles: self.spell_skip_roles.clone(),
+            match_log: self.match_log.load(Ordering::SeqCst),
+            in_game_guard: self.in_game_guard.load(Ordering::SeqCst),
+            champion_picks: self.champion_picks.clone(),
+            ban_picks: self.ban_picks.clone(),
+            pick_fallback: self.pick_fallback,
+            secondary_picks: self.secondary_picks.clone(),
+            trade_behavior: self.trade_behavior,
+            swap_behavior: self.swap_behavior,
+            hover_ban: self.hover_ban,
+            action_retries: self.action_retries,
+            max_picks: self.max_picks,
+            protect_picks: self.protect_picks,
+            autofill_picks_only: self.autofill_picks_only,
+            playable_picks: self.playable_picks,
+            live_game_stats: self.live_game_stats,
+            counter_ban: self.counter_ban,
+            counter_bans: self.counter_bans.clone(),
+            decoy_hover: self.decoy_hover,
+            decoy_ban: self.decoy_ban.clone(),
+            skip_ban: self.skip_ban,
+            winrate_picks: self.winrate_picks,
+            stats_url: self.stats_url.clone(),
+            quiet_hours: self.quiet_hours,
+            quiet_hours_start: self.quiet_hours_start.clone(),
+            quiet_hours_end: self.quiet_hours_end.clone(),
+            queue_automation: self.queue_automation.clone(),
+            ban_rules: self.ban_rules.clone(),
+            // the pick list adds an empty note for every pick, only written ones are kept
+            champion_notes: self
+                .champion_notes
+                .iter()
+                .filter(|(_, note)| !note.trim().is_empty())
+                .map(|(id, note)| (*id, note.clone()))
+                .collect(),
+            locale: self.champion_locale.lock().unwrap().clone(),
+            always_on_top: self.always_on_top,
+            minimize_on_close: self.minimize_on_close,
+            remember_window_position: self.remember_window_position,
+            battery_saver: self.battery_saver,
+            kill_switch_key: self.kill_switch_key.clone(),
+            practice_tool_cheats: self.practice_tool_cheats,
+            rune_page_selection: self.rune_page_selection.load(Ordering::SeqCst),
+            rune_pages: self.rune_pages.clone(),
+            per_account_picks: self.per_account_picks,
+            show_champion_alias: self.show_champion_alias,
+            account_profiles,
+            spell1: self.selected_image1.clone(),
+            spell2: self.selected_image2.clone(),
         }
     }
 }
 
 impl eframe::App for GUI {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                Event::Status(status) => self.gameflow_status = status,
+                Event::AssignedRole(role) => self.assigned_role = role,
+                Event::Phase(phase) => self.phase = phase,
+                Event::ChampSelectSession(session) => self.champ_select_session = Some(session),
+                Event::PickMode(pick_mode) => self.pick_mode = pick_mode,
+                Event::FeatureState(feature, state) => {
+                    self.feature_states[feature as usize] = state
+                }
+                Event::Action(action) => log_action(&mut self.action_log, action),
+                Event::ClearActions => self.action_log.clear(),
+                Event::Summoner(summoner) => {
+                    if summoner != self.active_account {
+                        self.switch_account(summoner);
+                    }
+                }
+                Event::Spells(spell1, spell2) => {
+                    self.selected_image1 = spell1;
+                    self.selected_image2 = spell2;
+                }
+                Event::CapturedRunePage(page) => self.rune_page_text = page,
+                Event::ChampionDataOutdated => self.champion_data_outdated = true,
+                Event::Invitations(invitations) => self.invitations = invitations,
+                Event::Latency(latency) => self.latency = Some(latency),
+                Event::Region(region) => self.region = Some(region),
+                Event::ChampionInventory(inventory) => self.champion_inventory = Some(inventory),
+                Event::ChampionIcon(id, bytes) => {
+                    self.champion_icons.insert_raw(id.to_string(), bytes)
+                }
+                Event::PositionPreferences(first, second) => {
+                    self.lobby_positions = Some((first, second))
+                }
+            }
+        }
+
+        // whole pixels so the UI state isn't rewritten for sub-pixel changes
+        if self.remember_window_position {
+            if let Some(position) = frame.info().window_info.position {
+                self.window_position = Some((position.x.round(), position.y.round()));
+            }
+        }
+
+        // the scale is relative to the monitor's own scaling, so 1.0 looks the same on every display
+        let pixels_per_point = frame.info().native_pixels_per_point.unwrap_or(1.0)
+            * self
+                .ui_scale
+                .clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end());
+        if (ctx.pixels_per_point() - pixels_per_point).abs() > f32::EPSILON {
+            ctx.set_pixels_per_point(pixels_per_point);
+        }
+
+        if std::mem::take(&mut self.minimize_requested) {
+            frame.set_minimized(true);
+        }
+
+        if std::mem::take(&mut self.reset_requested) {
+            self.load_settings(Settings::default());
+            self.ui_scale = UiState::default().ui_scale;
+            frame.set_always_on_top(self.always_on_top);
+        }
+
+        // persist any change made last frame, done before the locks below are taken
+        self.kill_switch_code.store(
+            hotkey_code(&self.kill_switch_key).unwrap_or(0),
+            Ordering::SeqCst,
+        );
+        let settings = self.settings();
+        if settings != self.saved_settings {
+            let _ = settings.save();
+            let _ = self.commands.send(Command::ApplySettings(settings.clone()));
+            self.saved_settings = settings;
+        }
+        // not written while a drag is still changing it every frame, once it's released or on exit
+        let ui_state = self.ui_state();
+        if ui_state != self.saved_ui_state && !ctx.input(|i| i.pointer.any_down()) {
+            let _ = ui_state.save();
+            self.saved_ui_state = ui_state;
+        }
+
         let pick_ban_selection = self.pick_ban_selection.load(Ordering::SeqCst);
+        if let Some(champions) = self.downloaded_champions.lock().unwrap().take() {
+            let _ = self.commands.send(Command::Champions(champions.clone()));
+            self.champions = champions;
+            self.champion_data_outdated = false;
+        }
+        let summoner_spells = self.summoner_spells.lock().unwrap().clone();
+        if summoner_spells != self.sent_summoner_spells {
+            let _ = self
+                .commands
+                .send(Command::SummonerSpells(summoner_spells.clone()));
+            self.sent_summoner_spells = summoner_spells;
+        }
+        for (name, bytes) in self.downloaded_spell_icons.lock().unwrap().drain(..) {
+            self.images.insert_raw(name, bytes);
+        }
         if let Some(timer) = self.clear_label_timer {
             let elapsed = timer.elapsed();
-            if elapsed.as_secs_f32() > 3.0 {
+            if elapsed.as_secs_f32() > 5.0 {
                 self.clear_label_timer = None;
+                self.cleared_picks = None;
             }
         }
         if let Some(timer) = self.pick_not_found_label_timer {
@@ -198,14 +1520,15 @@ impl eframe::App for GUI {
                 self.ban_not_found_label_timer = None;
             }
         }
-        let mut champion_picks = self.champion_picks.lock().unwrap();
-        let mut ban_picks = self.ban_picks.lock().unwrap();
+        let champion_picks = &mut self.champion_picks;
+        let ban_picks = &mut self.ban_picks;
         let connection_status = self.connection_status.lock().unwrap();
-        let gameflow_status = self.gameflow_status.lock().unwrap();
-        let mut selected_image1 = self.selected_image1.lock().unwrap();
-        let mut selected_image2 = self.selected_image2.lock().unwrap();
+        let gameflow_status = self.gameflow_status.clone();
+        let selected_image1 = &mut self.selected_image1;
+        let selected_image2 = &mut self.selected_image2;
         let update_status = self.update_status.lock().unwrap().clone();
         let current_version = self.current_version.lock().unwrap().clone();
+        let release_notes = self.release_notes.lock().unwrap().clone();
 
         egui::TopBottomPanel::top("top panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
@@ -216,15 +1539,86 @@ impl eframe::App for GUI {
                 }
 
                 ui.menu_button("File", |ui| {
-                    // TODO: add persistent settings
-                    // if ui.button("Save Settings").clicked() {
-
-                    // }
+                    if ui
+                        .checkbox(&mut self.always_on_top, "Always on Top")
+                        .changed()
+                    {
+                        frame.set_always_on_top(self.always_on_top);
+                    }
+                    ui.checkbox(&mut self.remember_window_position, "Remember Window Position")
+                        .on_hover_text("Opens the window where it was last, on the same monitor.");
+                    ui.add(egui::Slider::new(&mut self.ui_scale, UI_SCALE_RANGE).text("UI Scale"))
+                        .on_hover_text("Scales the whole window on top of the display scaling.");
+                    ui.checkbox(&mut self.battery_saver, "Battery Saver")
+                        .on_hover_text("Redraws less often outside of champion select.");
+                    ui.horizontal(|ui| {
+                        ui.label("Kill Switch: Ctrl+Shift+");
+                        ui.add(
+                            TextEdit::singleline(&mut self.kill_switch_key)
+                                .char_limit(1)
+                                .desired_width(16.0),
+                        )
+                        .on_hover_text(
+                            "Turns off all automation, even when this window isn't focused.",
+                        );
+                    });
+                    ui.checkbox(&mut self.debug_mode, "Developer Mode");
+                    ui.checkbox(&mut self.logs_open, "Show Logs");
 
-                    if ui.button("Quit").clicked() {
-                        frame.close();
+                    if ui
+                        .button("Export Diagnostics")
+                        .on_hover_text("Saves settings, version and recent actions for a bug report.")
+                        .clicked()
+                    {
+                        // saved_settings is what this frame started with, the live state is locked
+                        let result = export_diagnostics(
+                            &self.saved_settings,
+                            connection_status.as_deref(),
+                            &gameflow_status,
+                            &self.action_log,
+                        );
+                        self.diagnostics_status = Some(match result {
+                            Ok(path) => format!("Saved to {}", path.display()),
+                            Err(error) => format!("Couldn't save diagnostics: {}", error),
+                        });
+                    }
+                    if let Some(diagnostics_status) = &self.diagnostics_status {
+                        ui.weak(diagnostics_status);
                     }
-                });
+
+                    if ui
+                        .button("Edit Summoner Spells")
+                        .on_hover_text("Check or fix the spell ids used when setting spells.")
+                        .clicked()
+                    {
+                        self.spell_editor_open = true;
+                        self.spell_editor_status = None;
+                        ui.close_menu();
+                    }
+
+                    if ui
+                        .button("Export Plan as Image")
+                        .on_hover_text("Saves your picks, ban and spells as plan.png.")
+                        .clicked()
+                    {
+                        self.plan_window_open = true;
+                        self.plan_export_status = None;
+                        ui.close_menu();
+                    }
+
+                    let minimize_on_close_label = if self.minimize_on_close {
+                        "Minimize on Close: ON"
+                    } else {
+                        "Minimize on Close: OFF"
+                    };
+                    ui.checkbox(&mut self.minimize_on_close, minimize_on_close_label)
+                        .on_hover_text("The window's close button minimizes, Quit still exits.");
+
+                    if ui.button("Quit").clicked() {
+                        self.quit_requested = true;
+                        frame.close();
+                    }
+                });
 
                 if update_status.contains("outdated") {
                     if ui.button("Update").clicked() {
@@ -232,7 +1626,9 @@ impl eframe::App for GUI {
                         self.update.store(true, Ordering::SeqCst);
                     }
                     let asset_name = self.asset_name.lock().unwrap().clone();
-                    let asset_size = std::fs::metadata(&asset_name).unwrap().len();
+                    let asset_size = std::fs::metadata(&asset_name)
+                        .map(|metadata| metadata.len())
+                        .unwrap_or(0);
 
                     if self.update_button_clicked {
                         if asset_size / 1024 > 2000 {
@@ -245,10 +1641,38 @@ impl eframe::App for GUI {
                                     ui.label(
                                         "New update has been downloaded successfully to this program's folder.",
                                     );
-                                    ui.label("Press the close button to terminate the program.");
+                                    ui.label(
+                                        "Restart now to launch it, or close and start it yourself.",
+                                    );
 
-                                    if ui.button("Close").clicked() {
-                                        frame.close();
+                                    ui.horizontal(|ui| {
+                                        if ui.button("Restart now").clicked() {
+                                            match std::process::Command::new(&asset_name)
+                                                .current_dir(program_dir())
+                                                .spawn()
+                                            {
+                                                Ok(_) => {
+                                                    self.quit_requested = true;
+                                                    frame.close();
+                                                }
+                                                Err(error) => {
+                                                    self.restart_error = Some(format!(
+                                                        "Couldn't start the update: {}",
+                                                        error
+                                                    ))
+                                                }
+                                            }
+                                        }
+                                        if ui.button("Close").clicked() {
+                                            self.quit_requested = true;
+                                            frame.close();
+                                        }
+                                    });
+                                    if let Some(restart_error) = &self.restart_error {
+                                        ui.colored_label(
+                                            ui.visuals().error_fg_color,
+                                            restart_error,
+                                        );
                                     }
                             });
                         } else {
@@ -257,15 +1681,32 @@ impl eframe::App for GUI {
                     }
                 }
 
-                ui.add_space(ui.available_width() - 35.0);
+                if let Some((_, summoner_name)) = &self.active_account {
+                    ui.strong(summoner_name);
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.menu_button("About", |ui| {
+                        ui.label("circuit-watcher");
+                        ui.label(format!("version {}", current_version));
+                        if update_status == OFFLINE_STATUS {
+                            ui.weak(OFFLINE_STATUS);
+                        }
+                        ui.add(egui::Hyperlink::from_label_and_url(
+                            "source code",
+                            "https://github.com/TacticalDeuce/circuit-watcher",
+                        ));
 
-                ui.menu_button("About", |ui| {
-                    ui.label("circuit-watcher");
-                    ui.label(format!("version {}", current_version));
-                    ui.add(egui::Hyperlink::from_label_and_url(
-                        "source code",
-                        "https://github.com/TacticalDeuce/circuit-watcher",
-                    ));
+                        if let Some(notes) = &release_notes {
+                            ui.separator();
+                            ui.strong("What's new");
+                            egui::ScrollArea::vertical()
+                                .max_height(150.0)
+                                .show(ui, |ui| {
+                                    ui.label(notes);
+                                });
+                        }
+                    });
                 });
             });
         });
@@ -274,11 +1715,10 @@ impl eframe::App for GUI {
             .resizable(false)
             .exact_width(78.0)
             .show(ctx, |ui| {
-                let tabs = ["Settings", "Match State"];
                 ui.with_layout(
                     egui::Layout::top_down_justified(egui::Align::Center),
                     |ui| {
-                        for (idx, label) in tabs.iter().enumerate() {
+                        for (idx, label) in TABS.iter().enumerate() {
                             let button = ui.button(*label);
 
                             if self.active_tab != idx {
@@ -294,39 +1734,113 @@ impl eframe::App for GUI {
             });
 
         egui::CentralPanel::default().show(ctx, |ui| {
+            if self.automation_killed.load(Ordering::SeqCst) {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new("Automation disabled by the kill switch")
+                            .strong()
+                            .size(18.0)
+                            .color(ui.visuals().error_fg_color),
+                    );
+                    if ui.button("Dismiss").clicked() {
+                        self.automation_killed.store(false, Ordering::SeqCst);
+                    }
+                });
+                ui.separator();
+            }
             match self.active_tab {
                 0 => {
+                    if self.champion_data_outdated {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                "Champion data out of date, please update.",
+                            );
+                            if ui.button("Refresh Champion Data").clicked() {
+                                self.refresh_champions.store(true, Ordering::SeqCst);
+                            }
+                        });
+                    }
+
                     ui.horizontal(|ui| {
-                        if ui.button("Clear Picks/Bans").clicked() {
-                            champion_picks.clear();
-                            *ban_picks = None;
+                        // left alone while typing, Ctrl+Delete deletes a word in text fields
+                        let clear_shortcut = ui.memory(|memory| memory.focus().is_none())
+                            && ui.input_mut(|input| {
+                                input.consume_key(egui::Modifiers::CTRL, egui::Key::Delete)
+                            });
+                        if ui
+                            .button("Clear Picks/Bans")
+                            .on_hover_text("Ctrl+Delete")
+                            .clicked()
+                            || clear_shortcut
+                        {
+                            self.cleared_picks = Some((
+                                std::mem::take(&mut *champion_picks),
+                                std::mem::take(&mut *ban_picks),
+                                std::mem::take(&mut self.skip_ban),
+                            ));
                             self.clear_label_timer = Some(std::time::Instant::now());
                         }
+                        if ui.button("Reset Settings").clicked() {
+                            self.confirm_reset = true;
+                        }
                         if self.clear_label_timer.is_some() {
                             ui.strong("Picks and bans cleared.");
+                            if self.cleared_picks.is_some() && ui.small_button("Undo").clicked() {
+                                let (picks, ban, skip_ban) = self.cleared_picks.take().unwrap();
+                                *champion_picks = picks;
+                                *ban_picks = ban;
+                                self.skip_ban = skip_ban;
+                                self.clear_label_timer = None;
+                            }
                         }
                     });
 
+                    if self.confirm_reset {
+                        egui::Window::new("Reset Settings")
+                            .auto_sized()
+                            .anchor(egui::Align2::CENTER_CENTER, vec2(0.0, -25.0))
+                            .collapsible(false)
+                            .movable(false)
+                            .show(ctx, |ui| {
+                                ui.label(
+                                    "This clears all picks, bans and rune pages, turns every toggle off and resets the spells.",
+                                );
+                                ui.horizontal(|ui| {
+                                    if ui.button("Reset").clicked() {
+                                        self.reset_requested = true;
+                                        self.confirm_reset = false;
+                                    }
+                                    if ui.button("Cancel").clicked() {
+                                        self.confirm_reset = false;
+                                    }
+                                });
+                            });
+                    }
+
                     ui.horizontal(|ui| {
                         ui.menu_image_button(
                             selected_image1
                                 .clone()
                                 .as_ref()
-                                .and_then(|key| self.images.get(key))
-                                .map(|img| img.texture_id(ctx))
+                                .and_then(|key| self.images.texture_id(key, ctx))
                                 .unwrap_or(self.no_icon_img.texture_id(ctx)),
                             egui::vec2(20.0, 20.0),
                             |ui| {
                                 ui.horizontal(|ui| {
-                                    for (key, image) in &self.images {
+                                    for key in self.images.names() {
+                                        let Some(texture) = self.images.texture_id(&key, ctx)
+                                        else {
+                                            continue;
+                                        };
                                         if ui
                                             .add(egui::ImageButton::new(
-                                                image.texture_id(ctx),
+                                                texture,
                                                 egui::vec2(17.0, 17.0),
                                             ))
                                             .clicked()
                                         {
-                                            if key == &selected_image2.clone().unwrap_or_default() {
+                                            if key == selected_image2.clone().unwrap_or_default() {
                                                 let temp = selected_image2.clone();
                                                 *selected_image2 = selected_image1.clone();
                                                 *selected_image1 = temp;
@@ -340,25 +1854,37 @@ impl eframe::App for GUI {
                             },
                         );
 
+                        // slot order decides which spell is on D and which on F
+                        if ui
+                            .small_button("↔")
+                            .on_hover_text("Swap spell slots")
+                            .clicked()
+                        {
+                            std::mem::swap(&mut *selected_image1, &mut *selected_image2);
+                        }
+
                         ui.menu_image_button(
                             selected_image2
                                 .clone()
                                 .as_ref()
-                                .and_then(|key| self.images.get(key))
-                                .map(|img| img.texture_id(ctx))
+                                .and_then(|key| self.images.texture_id(key, ctx))
                                 .unwrap_or(self.no_icon_img.texture_id(ctx)),
                             egui::vec2(20.0, 20.0),
                             |ui| {
                                 ui.horizontal(|ui| {
-                                    for (key, image) in &self.images {
+                                    for key in self.images.names() {
+                                        let Some(texture) = self.images.texture_id(&key, ctx)
+                                        else {
+                                            continue;
+                                        };
                                         if ui
                                             .add(egui::ImageButton::new(
-                                                image.texture_id(ctx),
+                                                texture,
                                                 egui::vec2(17.0, 17.0),
                                             ))
                                             .clicked()
                                         {
-                                            if key == &selected_image1.clone().unwrap_or_default() {
+                                            if key == selected_image1.clone().unwrap_or_default() {
                                                 let temp = selected_image2.clone();
                                                 *selected_image2 = selected_image1.clone();
                                                 *selected_image1 = temp;
@@ -371,6 +1897,21 @@ impl eframe::App for GUI {
                                 });
                             },
                         );
+
+                        let suggestion = self
+                            .assigned_role
+                            .as_deref()
+                            .and_then(suggested_spells);
+                        if ui
+                            .add_enabled(suggestion.is_some(), egui::Button::new("Suggest spells"))
+                            .on_disabled_hover_text("Available once a role has been assigned.")
+                            .clicked()
+                        {
+                            if let Some((spell1, spell2)) = suggestion {
+                                *selected_image1 = Some(spell1.to_owned());
+                                *selected_image2 = Some(spell2.to_owned());
+                            }
+                        }
                     });
 
                     ui.horizontal(|ui| {
@@ -392,6 +1933,25 @@ impl eframe::App for GUI {
                         }
                     });
 
+                    if self.spell_selection.load(Ordering::SeqCst) {
+                        ui.horizontal(|ui| {
+                            ui.label("Roles:");
+                            for (position, label) in ROLES {
+                                let mut enabled =
+                                    !self.spell_skip_roles.iter().any(|role| role == position);
+                                if ui.checkbox(&mut enabled, label).changed() {
+                                    if enabled {
+                                        self.spell_skip_roles.retain(|role| role != position);
+                                    } else {
+                                        self.spell_skip_roles.push(position.to_owned());
+                                    }
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("Spells are only set for the checked roles.");
+                    }
+
                     if (selected_image1.clone().is_none() || selected_image2.clone().is_none())
                         && self.spell_selection.load(Ordering::SeqCst)
                     {
@@ -417,26 +1977,351 @@ impl eframe::App for GUI {
                         }
                     });
 
-                    // TODO:
-                    // ui.horizontal(|ui| {
-                    //     let rune_page_label = if self.rune_page_selection.load(Ordering::SeqCst) {
-                    //         "Rune Page Change: ON"
-                    //     } else {
-                    //         "Rune Page Change: OFF"
-                    //     };
-
-                    //     if ui
-                    //         .checkbox(
-                    //             &mut self.rune_page_selection.load(Ordering::SeqCst),
-                    //             rune_page_label,
-                    //         )
-                    //         .clicked()
-                    //     {
-                    //         let current_state = self.rune_page_selection.load(Ordering::SeqCst);
-                    //         self.rune_page_selection
-                    //             .store(!current_state, Ordering::SeqCst);
-                    //     }
-                    // });
+                    ui.horizontal(|ui| {
+                        let match_log_label = if self.match_log.load(Ordering::SeqCst) {
+                            "Match History Log: ON"
+                        } else {
+                            "Match History Log: OFF"
+                        };
+
+                        if ui
+                            .checkbox(&mut self.match_log.load(Ordering::SeqCst), match_log_label)
+                            .on_hover_text("Appends a summary of every game to utils/match_log.csv")
+                            .clicked()
+                        {
+                            let current_state = self.match_log.load(Ordering::SeqCst);
+                            self.match_log.store(!current_state, Ordering::SeqCst);
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        let live_game_stats_label = if self.live_game_stats {
+                            "Live Game Stats: ON"
+                        } else {
+                            "Live Game Stats: OFF"
+                        };
+                        ui.checkbox(&mut self.live_game_stats, live_game_stats_label)
+                            .on_hover_text(
+                                "Shows your KDA, CS and the game time in Match State while a game \
+                                 is running.",
+                            );
+                    });
+
+                    ui.horizontal(|ui| {
+                        let in_game_guard_label = if self.in_game_guard.load(Ordering::SeqCst) {
+                            "In-Game Safety Lock: ON"
+                        } else {
+                            "In-Game Safety Lock: OFF"
+                        };
+
+                        if ui
+                            .checkbox(
+                                &mut self.in_game_guard.load(Ordering::SeqCst),
+                                in_game_guard_label,
+                            )
+                            .on_hover_text(
+                                "Confirms the client is in ready check or champion select before \
+                                 any action is sent.",
+                            )
+                            .clicked()
+                        {
+                            let current_state = self.in_game_guard.load(Ordering::SeqCst);
+                            self.in_game_guard.store(!current_state, Ordering::SeqCst);
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        let quiet_hours_label = if self.quiet_hours {
+                            "Do Not Disturb: ON"
+                        } else {
+                            "Do Not Disturb: OFF"
+                        };
+
+                        ui.checkbox(&mut self.quiet_hours, quiet_hours_label)
+                            .on_hover_text("Pauses all automation between these times.");
+                        if self.quiet_hours {
+                            ui.add(
+                                TextEdit::singleline(&mut self.quiet_hours_start)
+                                    .desired_width(40.0)
+                                    .hint_text("HH:MM"),
+                            );
+                            ui.label("to");
+                            ui.add(
+                                TextEdit::singleline(&mut self.quiet_hours_end)
+                                    .desired_width(40.0)
+                                    .hint_text("HH:MM"),
+                            );
+                            if parse_clock_time(&self.quiet_hours_start).is_none()
+                                || parse_clock_time(&self.quiet_hours_end).is_none()
+                            {
+                                ui.colored_label(ui.visuals().warn_fg_color, "Invalid time");
+                            }
+                        }
+                    });
+
+                    egui::CollapsingHeader::new("Per-Queue Automation")
+                        .id_source("queue_automation")
+                        .show(ui, |ui| {
+                            ui.weak("Queues listed here use their own toggles instead of the ones above.");
+                            let mut queue_ids: Vec<u32> =
+                                self.queue_automation.keys().copied().collect();
+                            queue_ids.sort();
+                            let mut remove = None;
+                            egui::Grid::new("queue_automation_grid").show(ui, |ui| {
+                                for queue_id in queue_ids {
+                                    let queue = self.queue_automation.get_mut(&queue_id).unwrap();
+                                    ui.label(queue_name(queue_id));
+                                    ui.checkbox(&mut queue.auto_accept, "Accept");
+                                    ui.checkbox(&mut queue.pick_ban, "Pick/Ban");
+                                    ui.checkbox(&mut queue.spells, "Spells");
+                                    if ui.small_button("Remove").clicked() {
+                                        remove = Some(queue_id);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                            if let Some(queue_id) = remove {
+                                self.queue_automation.remove(&queue_id);
+                            }
+
+                            egui::ComboBox::from_id_source("add_queue_automation")
+                                .selected_text("Add queue")
+                                .show_ui(ui, |ui| {
+                                    for (queue_id, name) in QUEUES {
+                                        if self.queue_automation.contains_key(&queue_id) {
+                                            continue;
+                                        }
+                                        if ui.selectable_label(false, name).clicked() {
+                                            self.queue_automation
+                                                .insert(queue_id, QueueAutomation::default());
+                                        }
+                                    }
+                                });
+                        });
+
+                    egui::CollapsingHeader::new("Conditional Bans")
+                        .id_source("ban_rules")
+                        .show(ui, |ui| {
+                            ui.weak("The first matching rule is banned instead of your ban.");
+                            let role_name = |position: &Option<String>| {
+                                position.as_deref().map_or("Any Role".to_owned(), |position| {
+                                    ROLES
+                                        .iter()
+                                        .find(|(role, _)| *role == position)
+                                        .map_or(position.to_owned(), |(_, label)| label.to_string())
+                                })
+                            };
+                            let queue_label = |queue: Option<u32>| {
+                                queue.map_or("Any Queue".to_owned(), queue_name)
+                            };
+
+                            let mut remove = None;
+                            egui::Grid::new("ban_rules_grid").show(ui, |ui| {
+                                for (idx, rule) in self.ban_rules.iter().enumerate() {
+                                    ui.label(role_name(&rule.position));
+                                    ui.label(queue_label(rule.queue));
+                                    ui.label(&rule.champion.1);
+                                    if ui.small_button("Remove").clicked() {
+                                        remove = Some(idx);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                            if let Some(idx) = remove {
+                                self.ban_rules.remove(idx);
+                            }
+
+                            ui.horizontal(|ui| {
+                                egui::ComboBox::from_id_source("ban_rule_position")
+                                    .selected_text(role_name(&self.ban_rule_position))
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(
+                                            &mut self.ban_rule_position,
+                                            None,
+                                            "Any Role",
+                                        );
+                                        for (position, label) in ROLES {
+                                            ui.selectable_value(
+                                                &mut self.ban_rule_position,
+                                                Some(position.to_owned()),
+                                                label,
+                                            );
+                                        }
+                                    });
+                                egui::ComboBox::from_id_source("ban_rule_queue")
+                                    .selected_text(queue_label(self.ban_rule_queue))
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(
+                                            &mut self.ban_rule_queue,
+                                            None,
+                                            "Any Queue",
+                                        );
+                                        for (queue_id, name) in QUEUES {
+                                            ui.selectable_value(
+                                                &mut self.ban_rule_queue,
+                                                Some(queue_id),
+                                                name,
+                                            );
+                                        }
+                                    });
+                                ui.add(
+                                    TextEdit::singleline(&mut self.ban_rule_text)
+                                        .hint_text("Champion to ban.")
+                                        .desired_width(100.0),
+                                );
+                                if ui.button("Add").clicked() {
+                                    let name_cleaned = clean_champion_name(&self.ban_rule_text);
+                                    match self
+                                        .champions
+                                        .iter()
+                                        .find(|champion| champion.matches(&name_cleaned))
+                                    {
+                                        Some(champion) => {
+                                            self.ban_rules.push(BanRule {
+                                                position: self.ban_rule_position.clone(),
+                                                queue: self.ban_rule_queue,
+                                                champion: (champion.id, champion.name.clone()),
+                                            });
+                                            self.ban_rule_text.clear();
+                                            self.ban_rule_error = None;
+                                        }
+                                        None => {
+                                            self.ban_rule_error = Some(format!(
+                                                "No champion found with the name \"{}\".",
+                                                self.ban_rule_text.trim()
+                                            ));
+                                        }
+                                    }
+                                }
+                            });
+                            if let Some(error) = &self.ban_rule_error {
+                                ui.weak(error);
+                            }
+                        });
+
+                    let trade_behavior = &mut self.trade_behavior;
+                    egui::ComboBox::from_label("Incoming Trades")
+                        .selected_text(trade_behavior.label())
+                        .show_ui(ui, |ui| {
+                            for behavior in TradeBehavior::ALL {
+                                ui.selectable_value(
+                                    &mut *trade_behavior,
+                                    behavior,
+                                    behavior.label(),
+                                );
+                            }
+                        });
+
+                    egui::ComboBox::from_label("Incoming Position Swaps")
+                        .selected_text(self.swap_behavior.label())
+                        .show_ui(ui, |ui| {
+                            for behavior in SwapBehavior::ALL {
+                                ui.selectable_value(
+                                    &mut self.swap_behavior,
+                                    behavior,
+                                    behavior.label(),
+                                );
+                            }
+                        });
+
+                    let mut champion_locale = self.champion_locale.lock().unwrap();
+                    egui::ComboBox::from_label("Champion Name Language")
+                        .selected_text(champion_locale.as_str())
+                        .show_ui(ui, |ui| {
+                            for locale in LOCALES {
+                                ui.selectable_value(
+                                    &mut *champion_locale,
+                                    locale.to_owned(),
+                                    locale,
+                                );
+                            }
+                        });
+
+                    ui.horizontal(|ui| {
+                        let rune_page_label = if self.rune_page_selection.load(Ordering::SeqCst) {
+                            "Rune Page Change: ON"
+                        } else {
+                            "Rune Page Change: OFF"
+                        };
+
+                        if ui
+                            .checkbox(
+                                &mut self.rune_page_selection.load(Ordering::SeqCst),
+                                rune_page_label,
+                            )
+                            .clicked()
+                        {
+                            let current_state = self.rune_page_selection.load(Ordering::SeqCst);
+                            self.rune_page_selection
+                                .store(!current_state, Ordering::SeqCst);
+                        }
+                    });
+
+                    if self.rune_page_selection.load(Ordering::SeqCst) {
+                        ui.add(
+                            TextEdit::singleline(&mut self.rune_champion_text)
+                                .hint_text("Champion for this rune page"),
+                        );
+                        ui.add(
+                            TextEdit::multiline(&mut self.rune_page_text)
+                                .desired_rows(2)
+                                .hint_text(
+                                    "Rune page JSON, or: primary style, secondary style, 9 perk ids",
+                                ),
+                        );
+
+                        ui.horizontal(|ui| {
+                            if ui.button("Save Page").clicked() {
+                                let champion_text_cleaned =
+                                    clean_champion_name(&self.rune_champion_text);
+                                let matching_champion = self.champions.iter().find(|champion| {
+                                    champion.matches(&champion_text_cleaned)
+                                });
+
+                                self.rune_feedback = Some(match matching_champion {
+                                    Some(champion) => {
+                                        match parse_rune_page(&self.rune_page_text) {
+                                            Ok(page) => {
+                                                self.rune_pages.insert(champion.id, page);
+                                                self.rune_page_text.clear();
+                                                format!("Saved rune page for {}.", champion.name)
+                                            }
+                                            Err(error) => error,
+                                        }
+                                    }
+                                    None => "No champion found with the given name.".to_owned(),
+                                });
+                            }
+
+                            if ui.button("Capture Current Page").clicked() {
+                                let _ = self.commands.send(Command::CaptureRunePage);
+                            }
+                        });
+
+                        if let Some(feedback) = &self.rune_feedback {
+                            ui.weak(feedback);
+                        }
+
+                        let rune_pages = &mut self.rune_pages;
+                        let mut removed = None;
+                        for (champion_id, page) in rune_pages.iter() {
+                            let champion_name = self
+                                .champions
+                                .iter()
+                                .find(|champion| champion.id == *champion_id)
+                                .map(|champion| champion.name.clone())
+                                .unwrap_or_else(|| champion_id.to_string());
+                            ui.horizontal(|ui| {
+                                ui.label(format!("{}: {}", champion_name, page.name));
+                                if ui.small_button("Remove").clicked() {
+                                    removed = Some(*champion_id);
+                                }
+                            });
+                        }
+                        if let Some(champion_id) = removed {
+                            rune_pages.remove(&champion_id);
+                        }
+                    }
 
                     ui.horizontal(|ui| {
                         let pick_ban_label = if self.pick_ban_selection.load(Ordering::SeqCst) {
@@ -458,56 +2343,99 @@ impl eframe::App for GUI {
                         }
                     });
 
+                    ui.horizontal(|ui| {
+                        let per_account_label = if self.per_account_picks {
+                            "Per-Account Picks: ON"
+                        } else {
+                            "Per-Account Picks: OFF"
+                        };
+
+                        ui.checkbox(&mut self.per_account_picks, per_account_label)
+                            .on_hover_text(
+                                "Keep a separate pick and ban pool for each League account.",
+                            );
+                    });
+
+                    ui.horizontal(|ui| {
+                        let show_alias_label = if self.show_champion_alias {
+                            "Show Champion Alias: ON"
+                        } else {
+                            "Show Champion Alias: OFF"
+                        };
+
+                        ui.checkbox(&mut self.show_champion_alias, show_alias_label)
+                            .on_hover_text(
+                                "Shows the Data Dragon key next to names, e.g. MonkeyKing for Wukong.",
+                            );
+                    });
+
                     ui.vertical(|ui| {
                         if pick_ban_selection {
-                            if champion_picks.len() < 2 {
-                                ui.label("Enter champions to pick (2 max):");
+                            if champion_picks.len() < self.max_picks {
+                                ui.label(format!(
+                                    "Enter champions to pick ({} max):",
+                                    self.max_picks
+                                ));
                                 let text_edit_picks = ui.add(
                                     TextEdit::singleline(&mut self.pick_text)
                                         .hint_text("Press enter to skip."),
                                 );
+                                keep_entry_focus(ui, &text_edit_picks, &mut self.entry_focus);
 
                                 if !self.pick_text.is_empty() {
-                                    let pick_text_cleaned = self
-                                        .pick_text
-                                        .trim()
-                                        .replace(" ", "")
-                                        .as_str()
-                                        .replace("'", "")
-                                        .to_lowercase();
-
-                                    let matching_champions: Vec<String> = self
+                                    let pick_text_cleaned = clean_champion_name(&self.pick_text);
+
+                                    let matching_champions: Vec<(String, egui::RichText)> = self
                                         .champions
                                         .iter()
                                         .filter(|champion| {
-                                            champion
-                                                .name
-                                                .to_lowercase()
-                                                .starts_with(&pick_text_cleaned)
+                                            champion.matches_prefix(&pick_text_cleaned)
+                                        })
+                                        .map(|champion| {
+                                            let availability =
+                                                self.champion_inventory.as_ref().map(|inventory| {
+                                                    inventory
+                                                        .get(&champion.id)
+                                                        .copied()
+                                                        .unwrap_or(Availability::Unowned)
+                                                });
+                                            (
+                                                champion.name.clone(),
+                                                suggestion_text(
+                                                    champion.label(self.show_champion_alias),
+                                                    availability,
+                                                ),
+                                            )
                                         })
-                                        .map(|champion| champion.name.clone())
                                         .collect();
 
                                     if !matching_champions.is_empty() {
                                         ui.push_id("pick suggestion", |ui| {
                                             // this is done to ensure no id clash
-                                            eframe::egui::ComboBox::from_label("Name Suggestions")
-                                                .selected_text(matching_champions[0].clone())
-                                                .width(ui.available_width() / 3.0)
-                                                .show_ui(ui, |ui| {
-                                                    for suggestion in matching_champions {
-                                                        if ui
-                                                            .selectable_value(
-                                                                &mut self.pick_text,
-                                                                suggestion.clone(),
-                                                                suggestion,
-                                                            )
-                                                            .clicked()
-                                                        {
-                                                            text_edit_picks.request_focus();
-                                                        }
+                                            let suggestions = eframe::egui::ComboBox::from_label(
+                                                "Name Suggestions",
+                                            )
+                                            .selected_text(matching_champions[0].1.clone())
+                                            .width(ui.available_width() / 3.0)
+                                            .show_ui(ui, |ui| {
+                                                for (suggestion, label) in matching_champions {
+                                                    if ui
+                                                        .selectable_value(
+                                                            &mut self.pick_text,
+                                                            suggestion,
+                                                            label,
+                                                        )
+                                                        .clicked()
+                                                    {
+                                                        text_edit_picks.request_focus();
                                                     }
-                                                });
+                                                }
+                                            });
+                                            if self.champion_inventory.is_some() {
+                                                suggestions.response.on_hover_text(
+                                                    "(free): free rotation, greyed: not owned",
+                                                );
+                                            }
                                         });
                                     }
                                 }
@@ -515,17 +2443,11 @@ impl eframe::App for GUI {
                                 if text_edit_picks.lost_focus()
                                     && ui.input(|i| i.key_pressed(egui::Key::Enter))
                                 {
-                                    let pick_text_cleaned = self
-                                        .pick_text
-                                        .trim()
-                                        .replace(" ", "")
-                                        .as_str()
-                                        .replace("'", "")
-                                        .to_lowercase();
+                                    let pick_text_cleaned = clean_champion_name(&self.pick_text);
 
                                     let matching_champion =
                                         self.champions.iter().find(|champion| {
-                                            champion.name.to_lowercase() == pick_text_cleaned
+                                            champion.matches(&pick_text_cleaned)
                                         });
 
                                     if !pick_text_cleaned.is_empty() {
@@ -556,52 +2478,77 @@ impl eframe::App for GUI {
                                         champion_picks.push((0, "".to_string()));
                                     }
                                     self.pick_text.clear();
-                                    text_edit_picks.request_focus();
+                                    self.entry_focus = true;
+                                    if champion_picks.len() < self.max_picks {
+                                        text_edit_picks.request_focus();
+                                    } else {
+                                        // released so the ban box can take it next frame
+                                        ui.memory_mut(|memory| {
+                                            memory.surrender_focus(text_edit_picks.id)
+                                        });
+                                    }
                                 }
                                 if self.pick_not_found_label_timer.is_some() {
                                     ui.weak(&self.text);
                                 }
+                                if ui
+                                    .small_button("Import Owned Champions")
+                                    .on_hover_text("Choose picks from the champions you own.")
+                                    .clicked()
+                                {
+                                    self.owned_selection.clear();
+                                    self.owned_window_open = true;
+                                }
                             }
 
-                            if ban_picks.is_none() {
-                                ui.label("Enter champion to ban:");
+                            if !self.skip_ban {
+                                // later entries are only used in modes with several bans
+                                if ban_picks.is_empty() {
+                                    ui.label("Enter champion to ban:");
+                                } else {
+                                    ui.label("Enter a ban for your next ban turn:");
+                                }
                                 let text_edit_bans = ui.add(
-                                    TextEdit::singleline(&mut self.ban_text)
-                                        .hint_text("Press enter to skip."),
+                                    TextEdit::singleline(&mut self.ban_text).hint_text(
+                                        if ban_picks.is_empty() {
+                                            "Press enter to skip."
+                                        } else {
+                                            "Only used with several bans."
+                                        },
+                                    ),
                                 );
+                                if champion_picks.len() >= self.max_picks {
+                                    keep_entry_focus(ui, &text_edit_bans, &mut self.entry_focus);
+                                }
 
                                 if !self.ban_text.is_empty() {
-                                    let ban_text_cleaned = self
-                                        .ban_text
-                                        .trim()
-                                        .replace(" ", "")
-                                        .as_str()
-                                        .replace("'", "")
-                                        .to_lowercase();
-
-                                    let matching_champions: Vec<String> = self
+                                    let ban_text_cleaned = clean_champion_name(&self.ban_text);
+
+                                    let matching_champions: Vec<(String, String)> = self
                                         .champions
                                         .iter()
                                         .filter(|champion| {
-                                            champion
-                                                .name
-                                                .to_lowercase()
-                                                .starts_with(&ban_text_cleaned)
+                                            champion.matches_prefix(&ban_text_cleaned)
+                                        })
+                                        .map(|champion| {
+                                            (
+                                                champion.name.clone(),
+                                                champion.label(self.show_champion_alias),
+                                            )
                                         })
-                                        .map(|champion| champion.name.clone())
                                         .collect();
 
                                     if !matching_champions.is_empty() {
                                         eframe::egui::ComboBox::from_label("Name Suggestions")
-                                            .selected_text(matching_champions[0].clone())
+                                            .selected_text(matching_champions[0].1.clone())
                                             .width(ui.available_width() / 3.0)
                                             .show_ui(ui, |ui| {
-                                                for suggestion in matching_champions {
+                                                for (suggestion, label) in matching_champions {
                                                     if ui
                                                         .selectable_value(
                                                             &mut self.ban_text,
-                                                            suggestion.clone(),
                                                             suggestion,
+                                                            label,
                                                         )
                                                         .clicked()
                                                     {
@@ -615,17 +2562,11 @@ impl eframe::App for GUI {
                                 if text_edit_bans.lost_focus()
                                     && ui.input(|i| i.key_pressed(egui::Key::Enter))
                                 {
-                                    let ban_text_cleaned = self
-                                        .ban_text
-                                        .trim()
-                                        .replace(" ", "")
-                                        .as_str()
-                                        .replace("'", "")
-                                        .to_lowercase();
+                                    let ban_text_cleaned = clean_champion_name(&self.ban_text);
 
                                     let matching_champion =
                                         self.champions.iter().find(|champion| {
-                                            champion.name.to_lowercase() == ban_text_cleaned
+                                            champion.matches(&ban_text_cleaned)
                                         });
 
                                     if !ban_text_cleaned.is_empty() {
@@ -635,13 +2576,21 @@ impl eframe::App for GUI {
                                                     .contains(&(champion.id, champion.name.clone()))
                                                 {
                                                     self.text =
-                                                        "Champion has alread been selected."
+                                                        "Champion is one of your picks, it can't be banned."
+                                                            .to_string();
+                                                    self.ban_not_found_label_timer =
+                                                        Some(std::time::Instant::now());
+                                                } else if ban_picks
+                                                    .contains(&(champion.id, champion.name.clone()))
+                                                {
+                                                    self.text =
+                                                        "Champion is already one of your bans."
                                                             .to_string();
                                                     self.ban_not_found_label_timer =
                                                         Some(std::time::Instant::now());
                                                 } else {
-                                                    *ban_picks =
-                                                        Some((champion.id, champion.name.clone()));
+                                                    ban_picks
+                                                        .push((champion.id, champion.name.clone()));
                                                 }
                                             }
                                             None => {
@@ -652,20 +2601,16 @@ impl eframe::App for GUI {
                                                     Some(std::time::Instant::now());
                                             }
                                         }
-                                    } else {
-                                        *ban_picks = Some((
-                                            0,
-                                            self.ban_text
-                                                .trim()
-                                                .replace(" ", "")
-                                                .as_str()
-                                                .replace("'", "")
-                                                .to_string()
-                                                .to_lowercase(),
-                                        ));
+                                    } else if ban_picks.is_empty() {
+                                        // the ban action is left alone and runs out on its own
+                                        self.skip_ban = true;
                                     }
                                     self.ban_text.clear();
-                                    text_edit_bans.request_focus();
+                                    if ban_picks.is_empty() && !self.skip_ban {
+                                        text_edit_bans.request_focus();
+                                    } else {
+                                        self.entry_focus = false;
+                                    }
                                 }
                                 if self.ban_not_found_label_timer.is_some() {
                                     ui.weak(&self.text);
@@ -673,155 +2618,2433 @@ impl eframe::App for GUI {
                             }
                         }
                         if pick_ban_selection {
-                            if champion_picks.len() == 2
-                                && champion_picks.get(0).unwrap().1.is_empty()
-                                && ban_picks.is_some()
-                                && ban_picks.as_ref().unwrap().1.is_empty()
-                                && champion_picks.get(1).unwrap().1.is_empty()
-                            {
-                                champion_picks.clear();
-                                *ban_picks = None;
-                                self.pick_ban_selection.store(false, Ordering::SeqCst);
-                            }
+                            let alias_suffix = |id: u32| {
+                                self.champions
+                                    .iter()
+                                    .find(|champion| champion.id == id)
+                                    .filter(|champion| {
+                                        self.show_champion_alias && !champion.alias.is_empty()
+                                    })
+                                    .map_or(String::new(), |champion| {
+                                        format!(" Alias:\"{}\"", champion.alias)
+                                    })
+                            };
                             if champion_picks.len() != 0 {
                                 ui.strong("Picks:");
-                                for (id, name) in &*champion_picks {
-                                    if !name.is_empty() {
-                                        ui.label(format!("ID:{id} Name:\"{name}\""));
-                                    } else {
-                                        ui.label("None");
-                                    }
-                                }
-                            }
-                            if ban_picks.is_some() {
-                                ui.strong("Ban:");
-                                if ban_picks.as_ref().unwrap().1.is_empty() {
-                                    ui.label("None");
+                                if self.pick_mode == Some(PickMode::Separate) {
+                                    ui.weak("Several pick turns in this queue, each slot is locked on its own turn.");
+                                } else if self.winrate_picks {
+                                    ui.weak("Tried by win rate for your role, the first available champion is locked.");
                                 } else {
-                                    ui.label(format!(
-                                        "ID:{} Name:\"{}\"",
-                                        &ban_picks.as_ref().unwrap().0,
-                                        &ban_picks.as_ref().unwrap().1
-                                    ));
+                                    ui.weak("Tried in order, the first available champion is locked.");
                                 }
-                            }
-                        }
-                    });
-                }
-                1 => {
-                    ui.heading(format!("{}", gameflow_status.clone()));
-                    if let Some(assigned_role) = self.assigned_role.lock().unwrap().clone() {
-                        ui.label(format!("Role: {}", assigned_role));
-                    }
-                }
-                2 => {}
-                _ => unreachable!(),
-            }
+                                let mut swap = None;
+                                let last = champion_picks.len() - 1;
+                                for (idx, (id, name)) in champion_picks.iter().enumerate() {
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .add_enabled(idx > 0, egui::Button::new("⬆").small())
+                                            .clicked()
+                                        {
+                                            swap = Some((idx - 1, idx));
+                                        }
+                                        if ui
+                                            .add_enabled(idx < last, egui::Button::new("⬇").small())
+                                            .clicked()
+                                        {
+                                            swap = Some((idx, idx + 1));
+                                        }
 
-            ui.vertical_centered_justified(|ui| {
-                ui.add_space(ui.available_size().y - ui.spacing().item_spacing.y * 11.0);
-                ui.weak(update_status);
+                                        if !name.is_empty() {
+                                            ui.label(format!(
+                                                "ID:{id} Name:\"{name}\"{}",
+                                                alias_suffix(*id)
+                                            ));
+                                            let note = self.champion_notes.entry(*id).or_default();
+                                            ui.add(
+                                                TextEdit::singleline(note)
+                                                    .hint_text("Note")
+                                                    .desired_width(120.0),
+                                            )
+                                            .on_hover_text(
+                                                "Shown in Match State while you play this champion.",
+                                            );
+                                        } else {
+                                            ui.label("None");
+                                        }
+                                    });
+                                }
+                                if let Some((a, b)) = swap {
+                                    champion_picks.swap(a, b);
+                                }
+                            }
+                            if self.skip_ban {
+                                ui.horizontal(|ui| {
+                                    ui.strong("Ban:");
+                                    ui.label("Skipped");
+                                    if ui.small_button("Undo").clicked() {
+                                        self.skip_ban = false;
+                                    }
+                                });
+                            } else if !ban_picks.is_empty() {
+                                ui.strong("Bans:");
+                                if ban_picks.len() > 1 {
+                                    ui.weak("One per ban turn, a champion that's gone is passed over.");
+                                }
+                                let mut swap = None;
+                                let mut remove = None;
+                                let last = ban_picks.len() - 1;
+                                for (idx, (id, name)) in ban_picks.iter().enumerate() {
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .add_enabled(idx > 0, egui::Button::new("⬆").small())
+                                            .clicked()
+                                        {
+                                            swap = Some((idx - 1, idx));
+                                        }
+                                        if ui
+                                            .add_enabled(idx < last, egui::Button::new("⬇").small())
+                                            .clicked()
+                                        {
+                                            swap = Some((idx, idx + 1));
+                                        }
+                                        if name.is_empty() {
+                                            ui.label("None");
+                                        } else {
+                                            ui.label(format!(
+                                                "ID:{id} Name:\"{name}\"{}",
+                                                alias_suffix(*id)
+                                            ));
+                                        }
+                                        if ui.small_button("Remove").clicked() {
+                                            remove = Some(idx);
+                                        }
+                                    });
+                                }
+                                if let Some((a, b)) = swap {
+                                    ban_picks.swap(a, b);
+                                }
+                                if let Some(idx) = remove {
+                                    ban_picks.remove(idx);
+                                }
+                                // a pick added after the ban can end up as both
+                                if self.protect_picks
+                                    && champion_picks
+                                        .iter()
+                                        .any(|pick| ban_picks.iter().any(|ban| ban.0 == pick.0))
+                                {
+                                    ui.colored_label(
+                                        ui.visuals().warn_fg_color,
+                                        "A ban is also one of your picks, it won't be banned.",
+                                    );
+                                }
+                            }
+
+                            let protect_picks_label = if self.protect_picks {
+                                "Never Ban My Picks: ON"
+                            } else {
+                                "Never Ban My Picks: OFF"
+                            };
+                            ui.checkbox(&mut self.protect_picks, protect_picks_label)
+                                .on_hover_text(
+                                    "Skips any ban, including counter-bans, that is in your pick list.",
+                                );
+
+                            let autofill_picks_only_label = if self.autofill_picks_only {
+                                "Auto-Pick Only When Autofilled: ON"
+                            } else {
+                                "Auto-Pick Only When Autofilled: OFF"
+                            };
+                            ui.checkbox(&mut self.autofill_picks_only, autofill_picks_only_label)
+                                .on_hover_text(
+                                    "Leaves the pick to you when you get one of your lobby positions.",
+                                );
+
+                            let playable_picks_label = if self.playable_picks {
+                                "Only Owned or Free Picks: ON"
+                            } else {
+                                "Only Owned or Free Picks: OFF"
+                            };
+                            ui.checkbox(&mut self.playable_picks, playable_picks_label)
+                                .on_hover_text(
+                                    "Skips picks you don't own and that aren't in this week's free rotation.",
+                                );
+
+                            let hover_ban_label = if self.hover_ban {
+                                "Hover Ban Before Locking: ON"
+                            } else {
+                                "Hover Ban Before Locking: OFF"
+                            };
+                            ui.checkbox(&mut self.hover_ban, hover_ban_label)
+                                .on_hover_text(
+                                    "Shows the ban to your team first and locks it when the ban timer is almost up.",
+                                );
+
+                            ui.horizontal(|ui| {
+                                ui.label("Pick/Ban Retries:");
+                                ui.add(
+                                    egui::DragValue::new(&mut self.action_retries)
+                                        .clamp_range(0..=5),
+                                )
+                                .on_hover_text(
+                                    "How many more times a pick or ban the client rejects is sent before giving up.",
+                                );
+                            });
+
+                            ui.horizontal(|ui| {
+                                ui.label("Max Picks:");
+                                ui.add(
+                                    egui::DragValue::new(&mut self.max_picks).clamp_range(1..=10),
+                                )
+                                .on_hover_text("How many champions the pick list holds.");
+                            });
+
+                            let counter_ban_label = if self.counter_ban {
+                                "Counter-Ban: ON"
+                            } else {
+                                "Counter-Ban: OFF"
+                            };
+                            ui.checkbox(&mut self.counter_ban, counter_ban_label)
+                                .on_hover_text(
+                                    "Bans a champion from the watchlist when an enemy hovers it, otherwise your ban is used.",
+                                );
+                            if self.counter_ban {
+                                let text_edit_counter_ban = ui.add(
+                                    TextEdit::singleline(&mut self.counter_ban_text)
+                                        .hint_text("Watchlist, separated by commas."),
+                                );
+
+                                if text_edit_counter_ban.lost_focus()
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                                {
+                                    self.counter_bans.clear();
+                                    self.counter_ban_error = None;
+                                    for name in self.counter_ban_text.split(',') {
+                                        let name_cleaned = clean_champion_name(name);
+                                        if name_cleaned.is_empty() {
+                                            continue;
+                                        }
+
+                                        match self.champions.iter().find(|champion| {
+                                            champion.matches(&name_cleaned)
+                                        }) {
+                                            Some(champion) => {
+                                                if !self
+                                                    .counter_bans
+                                                    .contains(&(champion.id, champion.name.clone()))
+                                                {
+                                                    self.counter_bans
+                                                        .push((champion.id, champion.name.clone()));
+                                                }
+                                            }
+                                            None => {
+                                                self.counter_ban_error = Some(format!(
+                                                    "No champion found with the name \"{}\".",
+                                                    name.trim()
+                                                ));
+                                            }
+                                        }
+                                    }
+                                }
+                                if let Some(error) = &self.counter_ban_error {
+                                    ui.weak(error);
+                                }
+
+                                if !self.counter_bans.is_empty() {
+                                    let names: Vec<&str> = self
+                                        .counter_bans
+                                        .iter()
+                                        .map(|(_, name)| name.as_str())
+                                        .collect();
+                                    ui.label(format!("Watchlist: {}", names.join(", ")));
+                                }
+                            }
+
+                            let decoy_hover_label = if self.decoy_hover {
+                                "Decoy Ban Hover: ON"
+                            } else {
+                                "Decoy Ban Hover: OFF"
+                            };
+                            ui.checkbox(&mut self.decoy_hover, decoy_hover_label)
+                                .on_hover_text(
+                                    "Hovers another champion during your ban turn and bans the real one when the timer is almost up.",
+                                );
+                            if self.decoy_hover {
+                                let text_edit_decoy_ban = ui.add(
+                                    TextEdit::singleline(&mut self.decoy_ban_text)
+                                        .hint_text("Champion to hover."),
+                                );
+
+                                if text_edit_decoy_ban.lost_focus()
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                                {
+                                    let name_cleaned = clean_champion_name(&self.decoy_ban_text);
+                                    match self
+                                        .champions
+                                        .iter()
+                                        .find(|champion| champion.matches(&name_cleaned))
+                                    {
+                                        Some(champion) => {
+                                            self.decoy_ban =
+                                                Some((champion.id, champion.name.clone()));
+                                            self.decoy_ban_text.clear();
+                                            self.decoy_ban_error = None;
+                                        }
+                                        None => {
+                                            self.decoy_ban_error = Some(format!(
+                                                "No champion found with the name \"{}\".",
+                                                self.decoy_ban_text.trim()
+                                            ));
+                                        }
+                                    }
+                                }
+                                if let Some(error) = &self.decoy_ban_error {
+                                    ui.weak(error);
+                                }
+
+                                if let Some((_, name)) = &self.decoy_ban {
+                                    ui.label(format!("Decoy: {}", name));
+                                }
+                            }
+
+                            let winrate_picks_label = if self.winrate_picks {
+                                "Win Rate Pick Order: ON"
+                            } else {
+                                "Win Rate Pick Order: OFF"
+                            };
+                            ui.checkbox(&mut self.winrate_picks, winrate_picks_label)
+                                .on_hover_text(
+                                    "Tries the picks with the highest win rate for your role first.",
+                                );
+                            if self.winrate_picks {
+                                ui.add(
+                                    TextEdit::singleline(&mut self.stats_url)
+                                        .hint_text("Stats JSON URL"),
+                                )
+                                .on_hover_text(
+                                    "A JSON object mapping champion ids to win rates per position, \
+                                     e.g. {\"103\": {\"middle\": 51.2, \"all\": 50.4}}",
+                                );
+                            }
+
+                            let pick_fallback = &mut self.pick_fallback;
+                            egui::ComboBox::from_label("If all picks are unavailable")
+                                .selected_text(pick_fallback.label())
+                                .show_ui(ui, |ui| {
+                                    for fallback in PickFallback::ALL {
+                                        ui.selectable_value(
+                                            &mut *pick_fallback,
+                                            fallback,
+                                            fallback.label(),
+                                        );
+                                    }
+                                });
+
+                            if *pick_fallback == PickFallback::SecondaryPool {
+                                let secondary_picks = &mut self.secondary_picks;
+                                let text_edit_secondary = ui.add(
+                                    TextEdit::singleline(&mut self.secondary_text)
+                                        .hint_text("Secondary pool, separated by commas."),
+                                );
+
+                                if text_edit_secondary.lost_focus()
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                                {
+                                    secondary_picks.clear();
+                                    for name in self.secondary_text.split(',') {
+                                        let name_cleaned = clean_champion_name(name);
+                                        if name_cleaned.is_empty() {
+                                            continue;
+                                        }
+
+                                        match self.champions.iter().find(|champion| {
+                                            champion.matches(&name_cleaned)
+                                        }) {
+                                            Some(champion) => {
+                                                if !secondary_picks
+                                                    .contains(&(champion.id, champion.name.clone()))
+                                                {
+                                                    secondary_picks
+                                                        .push((champion.id, champion.name.clone()));
+                                                }
+                                            }
+                                            None => {
+                                                self.text = format!(
+                                                    "No champion found with the name \"{}\".",
+                                                    name.trim()
+                                                );
+                                                self.pick_not_found_label_timer =
+                                                    Some(std::time::Instant::now());
+                                            }
+                                        }
+                                    }
+                                }
+
+                                if !secondary_picks.is_empty() {
+                                    let names: Vec<&str> = secondary_picks
+                                        .iter()
+                                        .map(|(_, name)| name.as_str())
+                                        .collect();
+                                    ui.label(format!("Secondary pool: {}", names.join(", ")));
+                                }
+                            }
+                        }
+                    });
+                }
+                1 => {
+                    // Same toggles as the Settings tab, to flip them without switching tabs.
+                    ui.horizontal(|ui| {
+                        for (toggle, label) in [
+                            (&self.auto_accept, "Auto Accept"),
+                            (&self.pick_ban_selection, "Auto-Pick/Ban"),
+                        ] {
+                            let enabled = toggle.load(Ordering::SeqCst);
+                            let label =
+                                format!("{}: {}", label, if enabled { "ON" } else { "OFF" });
+                            if ui.checkbox(&mut { enabled }, label).clicked() {
+                                toggle.store(!enabled, Ordering::SeqCst);
+                            }
+                        }
+                    });
+                    ui.separator();
+                    ui.heading(format!("{}", gameflow_status.clone()));
+                    if let Some(assigned_role) = &self.assigned_role {
+                        ui.label(format!("Role: {}", assigned_role));
+                    }
+                    if let Some(latency) = self.latency {
+                        ui.weak(format!("Client latency: {} ms", latency.as_millis()));
+                    }
+                    // kept after champion select too, as a reminder during the game
+                    let note = self
+                        .champ_select_session
+                        .as_ref()
+                        .and_then(my_champion)
+                        .and_then(|id| {
+                            let note = self.champion_notes.get(&id)?;
+                            let champion =
+                                self.champions.iter().find(|champion| champion.id == id)?;
+                            Some((champion.name.clone(), note.trim()))
+                        })
+                        .filter(|(_, note)| !note.is_empty());
+                    if let Some((champion, note)) = note {
+                        ui.label(format!("{}: {}", champion, note));
+                    }
+                    if let Some((_, summoner_name)) = &self.active_account {
+                        ui.horizontal(|ui| {
+                            ui.label(summoner_name);
+                            if ui.small_button("Copy").clicked() {
+                                ui.output_mut(|o| o.copied_text = summoner_name.clone());
+                            }
+                            if let Some((op_gg, u_gg)) = self
+                                .region
+                                .as_deref()
+                                .and_then(|region| profile_urls(region, summoner_name))
+                            {
+                                ui.hyperlink_to("op.gg", op_gg);
+                                ui.hyperlink_to("u.gg", u_gg);
+                            }
+                        });
+                    }
+
+                    if !self.invitations.is_empty() {
+                        ui.separator();
+                        ui.strong("Lobby invitations");
+                        for invitation in &self.invitations {
+                            ui.horizontal(|ui| {
+                                ui.label(&invitation.fromSummonerName);
+                                if ui.small_button("Accept").clicked() {
+                                    let _ = self.commands.send(Command::AnswerInvitation(
+                                        invitation.invitationId.clone(),
+                                        true,
+                                    ));
+                                }
+                                if ui.small_button("Decline").clicked() {
+                                    let _ = self.commands.send(Command::AnswerInvitation(
+                                        invitation.invitationId.clone(),
+                                        false,
+                                    ));
+                                }
+                            });
+                        }
+                    }
+
+                    ui.separator();
+                    egui::Grid::new("feature_states_grid").show(ui, |ui| {
+                        for feature in Feature::ALL {
+                            let state = self.feature_states[feature as usize];
+                            ui.label(feature.label());
+                            match state {
+                                FeatureState::Error => {
+                                    ui.colored_label(ui.visuals().error_fg_color, state.label())
+                                }
+                                FeatureState::Fired => ui.strong(state.label()),
+                                FeatureState::Armed => ui.label(state.label()),
+                                FeatureState::Idle => ui.weak(state.label()),
+                            };
+                            ui.end_row();
+                        }
+                    });
+
+                    // the client only creates a lobby outside of queues and games
+                    if matches!(self.phase.as_deref(), Some("None" | "Lobby")) {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button("Start Practice Tool")
+                                .on_hover_text("Creates a practice game and starts it.")
+                                .clicked()
+                            {
+                                let _ = self
+                                    .commands
+                                    .send(Command::StartPracticeTool(self.practice_tool_cheats));
+                            }
+                            let cheats_label = if self.practice_tool_cheats {
+                                "Cheats: ON"
+                            } else {
+                                "Cheats: OFF"
+                            };
+                            ui.checkbox(&mut self.practice_tool_cheats, cheats_label)
+                                .on_hover_text("OFF starts a normal custom game instead.");
+                        });
+                    }
+
+                    if self.phase.as_deref() == Some("Lobby") {
+                        if let Some((first, second)) = &mut self.lobby_positions {
+                            let options: Vec<String> = ROLES
+                                .iter()
+                                .map(|(role, _)| role.to_uppercase())
+                                .chain(["FILL".to_owned()])
+                                .collect();
+                            let mut changed = false;
+                            ui.horizontal(|ui| {
+                                let preferences = [
+                                    ("Primary Role", &mut *first),
+                                    ("Secondary Role", &mut *second),
+                                ];
+                                for (label, preference) in preferences {
+                                    egui::ComboBox::from_label(label)
+                                        .selected_text(preference_label(preference))
+                                        .show_ui(ui, |ui| {
+                                            for position in &options {
+                                                changed |= ui
+                                                    .selectable_value(
+                                                        preference,
+                                                        position.clone(),
+                                                        preference_label(position),
+                                                    )
+                                                    .changed();
+                                            }
+                                        });
+                                }
+                            });
+                            if changed {
+                                let _ = self.commands.send(Command::SetPositionPreferences(
+                                    first.clone(),
+                                    second.clone(),
+                                ));
+                            }
+                        }
+                    }
+
+                    if self.phase.as_deref() == Some("ChampSelect") {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(!champion_picks.is_empty(), egui::Button::new("Pick Now"))
+                                .on_hover_text("Locks your first available pick on your pick turn.")
+                                .clicked()
+                            {
+                                let _ = self.commands.send(Command::PickNow);
+                            }
+                            if ui
+                                .add_enabled(
+                                    !ban_picks.is_empty() && !self.skip_ban,
+                                    egui::Button::new("Ban Now"),
+                                )
+                                .on_hover_text("Locks your ban on your ban turn.")
+                                .clicked()
+                            {
+                                let _ = self.commands.send(Command::BanNow);
+                            }
+                        });
+                    }
+
+                    let session = self
+                        .champ_select_session
+                        .as_ref()
+                        .filter(|_| self.phase.as_deref() == Some("ChampSelect"));
+                    if let Some(session) = session {
+                        let turns = turn_order(session);
+                        if !turns.is_empty() {
+                            ui.separator();
+                            ui.strong("Turn order");
+                            egui::ScrollArea::vertical()
+                                .id_source("turn_order")
+                                .max_height(160.0)
+                                .show(ui, |ui| {
+                                    egui::Grid::new("turn_order_grid").striped(true).show(
+                                        ui,
+                                        |ui| {
+                                            for (turn, actions) in turns.iter().enumerate() {
+                                                for action in actions {
+                                                    let mine = action.actorCellId
+                                                        == session["localPlayerCellId"];
+                                                    let player = if mine {
+                                                        "You".to_owned()
+                                                    } else if action.isAllyAction {
+                                                        format!("Ally {}", action.actorCellId)
+                                                    } else {
+                                                        format!("Enemy {}", action.actorCellId)
+                                                    };
+                                                    let champion = self
+                                                        .champions
+                                                        .iter()
+                                                        .find(|champion| {
+                                                            champion.id == action.championId
+                                                        })
+                                                        .map_or("-", |champion| {
+                                                            champion.name.as_str()
+                                                        });
+                                                    let state = if action.completed {
+                                                        "Done"
+                                                    } else if action.isInProgress {
+                                                        "Now"
+                                                    } else {
+                                                        ""
+                                                    };
+                                                    for text in [
+                                                        (turn + 1).to_string(),
+                                                        action.r#type.clone(),
+                                                        player,
+                                                        champion.to_owned(),
+                                                        state.to_owned(),
+                                                    ] {
+                                                        if mine {
+                                                            ui.colored_label(
+                                                                ui.visuals().warn_fg_color,
+                                                                egui::RichText::new(text).strong(),
+                                                            );
+                                                        } else {
+                                                            ui.label(text);
+                                                        }
+                                                    }
+                                                    ui.end_row();
+                                                }
+                                            }
+                                        },
+                                    );
+                                });
+                        }
+
+                        let enemies = enemy_champions(session);
+                        if !enemies.is_empty() {
+                            ui.separator();
+                            ui.strong("Enemy team");
+                            ui.horizontal_wrapped(|ui| {
+                                for id in enemies {
+                                    let champion = id.and_then(|id| {
+                                        self.champions.iter().find(|champion| champion.id == id)
+                                    });
+                                    let Some(champion) = champion else {
+                                        ui.weak("Picking…");
+                                        continue;
+                                    };
+                                    let icon = champion.id.to_string();
+                                    let texture = self.champion_icons.texture_id(&icon, ctx);
+                                    if let Some(texture) = texture {
+                                        ui.image(texture, egui::vec2(24.0, 24.0));
+                                    }
+                                    ui.label(&champion.name);
+                                }
+                            });
+                        }
+                    }
+
+                    if !self.action_log.is_empty() {
+                        ui.separator();
+                        ui.strong("Recent actions");
+                        egui::ScrollArea::vertical()
+                            .max_height(120.0)
+                            .stick_to_bottom(true)
+                            .show(ui, |ui| {
+                                for action in &self.action_log {
+                                    ui.label(action);
+                                }
+                            });
+                    }
+
+                    if self.debug_mode {
+                        let session = self.champ_select_session.clone();
+                        // the raw session is what most pick/ban bug reports hinge on
+                        if ui
+                            .add_enabled(
+                                session.is_some(),
+                                egui::Button::new("Copy Champ Select JSON"),
+                            )
+                            .clicked()
+                        {
+                            if let Some(session) = session {
+                                ui.output_mut(|o| {
+                                    o.copied_text =
+                                        serde_json::to_string_pretty(&session).unwrap_or_default()
+                                });
+                            }
+                        }
+                    }
+                }
+                2 => {}
+                _ => unreachable!(),
+            }
+
+            // laid out from the bottom edge, so the connection status comes first
+            ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
                 if let Some(status) = connection_status.clone() {
                     ui.weak(status.clone());
+                } else {
+                    // the lockfile hasn't been checked yet
+                    ui.weak("Connecting to LeagueClient…");
+                }
+                ui.weak(update_status);
+            });
+        });
+
+        if !self.startup_problems.is_empty() {
+            egui::Window::new("Startup Problems")
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    for problem in &self.startup_problems {
+                        ui.colored_label(ui.visuals().warn_fg_color, problem);
+                    }
+                    if ui.button("Dismiss").clicked() {
+                        self.startup_problems.clear();
+                    }
+                });
+        }
+
+        if self.logs_open {
+            egui::Window::new("Logs")
+                .open(&mut self.logs_open)
+                .default_size(vec2(420.0, 240.0))
+                .show(ctx, |ui| {
+                    let logs = LOGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    if ui.button("Copy").clicked() {
+                        let text = logs.iter().cloned().collect::<Vec<String>>().join("\n");
+                        ui.output_mut(|o| o.copied_text = text);
+                    }
+                    egui::ScrollArea::vertical()
+                        .stick_to_bottom(true)
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| {
+                            for line in logs.iter() {
+                                ui.monospace(line);
+                            }
+                        });
+                });
+        }
+
+        if self.spell_editor_open {
+            let mut open = true;
+            egui::Window::new("Summoner Spells")
+                .open(&mut open)
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    ui.label("The key is the spell id sent to the client when setting spells.");
+                    let mut summoner_spells = self.summoner_spells.lock().unwrap();
+                    let mut remove = None;
+                    egui::ScrollArea::vertical()
+                        .max_height(240.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("summoner_spells_grid")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.strong("Id");
+                                    ui.strong("Key");
+                                    ui.strong("Name");
+                                    ui.end_row();
+                                    for (idx, spell) in summoner_spells.iter_mut().enumerate() {
+                                        ui.add(
+                                            TextEdit::singleline(&mut spell.id)
+                                                .desired_width(120.0),
+                                        );
+                                        ui.add(egui::DragValue::new(&mut spell.key));
+                                        ui.add(
+                                            TextEdit::singleline(&mut spell.name)
+                                                .desired_width(100.0),
+                                        );
+                                        if ui.small_button("Remove").clicked() {
+                                            remove = Some(idx);
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    if let Some(idx) = remove {
+                        summoner_spells.remove(idx);
+                    }
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Add").clicked() {
+                            summoner_spells.push(SummonerSpell {
+                                id: String::new(),
+                                key: 0,
+                                name: String::new(),
+                            });
+                        }
+                        if ui.button("Save").clicked() {
+                            self.spell_editor_status =
+                                Some(match save_summoner_spells(&summoner_spells) {
+                                    Ok(()) => "Saved summoner_spells.json".to_owned(),
+                                    Err(error) => format!("Couldn't save: {}", error),
+                                });
+                        }
+                    });
+                    if let Some(status) = &self.spell_editor_status {
+                        ui.weak(status);
+                    }
+                });
+            self.spell_editor_open = open;
+        }
+
+        if self.owned_window_open {
+            let mut open = true;
+            let free_slots = self.max_picks.saturating_sub(champion_picks.len());
+            egui::Window::new("Import Owned Champions")
+                .open(&mut open)
+                .collapsible(false)
+                .show(ctx, |ui| match &self.champion_inventory {
+                    // read from the client once the summoner is known
+                    None => {
+                        ui.label("Your champions haven't been loaded from the client yet.");
+                    }
+                    Some(inventory) => {
+                        let mut choices: Vec<&Champion> = self
+                            .champions
+                            .iter()
+                            .filter(|champion| {
+                                inventory.get(&champion.id) == Some(&Availability::Owned)
+                            })
+                            .filter(|champion| {
+                                !champion_picks.iter().any(|(id, _)| *id == champion.id)
+                            })
+                            .collect();
+                        choices.sort_by(|a, b| a.name.cmp(&b.name));
+                        ui.label(format!("Select up to {} champions to add.", free_slots));
+                        egui::ScrollArea::vertical()
+                            .max_height(300.0)
+                            .show(ui, |ui| {
+                                for champion in &choices {
+                                    let mut selected = self.owned_selection.contains(&champion.id);
+                                    let full = self.owned_selection.len() >= free_slots;
+                                    if ui
+                                        .add_enabled(
+                                            selected || !full,
+                                            egui::Checkbox::new(
+                                                &mut selected,
+                                                champion.label(self.show_champion_alias),
+                                            ),
+                                        )
+                                        .changed()
+                                    {
+                                        if selected {
+                                            self.owned_selection.insert(champion.id);
+                                        } else {
+                                            self.owned_selection.remove(&champion.id);
+                                        }
+                                    }
+                                }
+                            });
+                        ui.separator();
+                        if ui
+                            .add_enabled(
+                                !self.owned_selection.is_empty(),
+                                egui::Button::new("Add Selected"),
+                            )
+                            .clicked()
+                        {
+                            for champion in &choices {
+                                if self.owned_selection.contains(&champion.id) {
+                                    champion_picks.push((champion.id, champion.name.clone()));
+                                }
+                            }
+                            self.owned_selection.clear();
+                            self.owned_window_open = false;
+                        }
+                    }
+                });
+            self.owned_window_open &= open;
+        }
+
+        if self.plan_window_open {
+            let mut open = true;
+            let picks = champion_picks.clone();
+            let bans = ban_picks.clone();
+            let spells = [selected_image1.clone(), selected_image2.clone()];
+            egui::Window::new("Pick/Ban Plan")
+                .open(&mut open)
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    self.plan_rect = Some(plan_grid(ui, &mut self.images, &picks, &bans, &spells));
+
+                    ui.separator();
+                    if ui.button("Save as PNG").clicked() {
+                        frame.request_screenshot();
+                        self.plan_export_requested = true;
+                    }
+                    if let Some(status) = &self.plan_export_status {
+                        ui.weak(status);
+                    }
+                });
+            self.plan_window_open = open;
+        }
+        self.pixels_per_point = ctx.pixels_per_point();
+
+        ctx.request_repaint_after(repaint_interval(self.phase.as_deref(), self.battery_saver));
+    }
+
+    /// Crops the plan grid out of the screenshot requested by "Save as PNG" and writes it to disk.
+    fn post_rendering(&mut self, _window_size_px: [u32; 2], frame: &eframe::Frame) {
+        if !self.plan_export_requested {
+            return;
+        }
+        let (Some(screenshot), Some(rect)) = (frame.screenshot(), self.plan_rect) else {
+            return;
+        };
+        self.plan_export_requested = false;
+        let plan = screenshot.region(&rect.expand(8.0), Some(self.pixels_per_point));
+        self.plan_export_status = Some(match save_plan_image(&plan) {
+            Ok(path) => format!("Saved to {}", path.display()),
+            Err(error) => format!("Couldn't save the plan: {}", error),
+        });
+    }
+
+    /// The window's close button only minimizes with "Minimize on Close" on, closing from the app
+    /// itself (File > Quit, the update dialog) always exits.
+    fn on_close_event(&mut self) -> bool {
+        if self.minimize_on_close && !self.quit_requested {
+            self.minimize_requested = true;
+            return false;
+        }
+        true
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        let ui_state = self.ui_state();
+        if ui_state != self.saved_ui_state {
+            let _ = ui_state.save();
+        }
+        std::process::exit(0);
+    }
+}
+
+async fn update_checker(
+    update_status: Arc<Mutex<String>>,
+    release_notes: Arc<Mutex<Option<String>>>,
+) -> Result<String, Box<dyn Error>> {
+    let repo_owner = "tacticaldeuce";
+    let repo_name = "circuit-watcher";
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/releases/latest",
+        repo_owner, repo_name
+    );
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(&url)
+        .header(
+            "User-Agent",
+            format!("CircuitWatcher/{} (Rust)", env!("CARGO_PKG_VERSION")),
+        )
+        .send()
+        .await?;
+    let json = response.json::<serde_json::Value>().await?;
+
+    let current_version = env!("CARGO_PKG_VERSION");
+
+    // When the API rate limit is hit GitHub answers with a `message`/`documentation_url` object
+    // instead of a release, so there is no `tag_name` to compare against.
+    if json.get("message").is_some() || json.get("documentation_url").is_some() {
+        *update_status.lock().unwrap() = "Update check rate-limited, try later".to_owned();
+        return Ok(current_version.to_owned());
+    }
+
+    let latest_tag = match json["tag_name"].as_str() {
+        Some(tag) => tag,
+        None => {
+            *update_status.lock().unwrap() = "Update check failed.".to_owned();
+            return Ok(current_version.to_owned());
+        }
+    };
+
+    let mut update_status = update_status.lock().unwrap();
+
+    if is_outdated(current_version, latest_tag) {
+        *update_status =
+            format!("Program is outdated the latest version is {}", latest_tag).to_owned();
+        // the release body is the markdown changelog written for the release
+        *release_notes.lock().unwrap() = json["body"]
+            .as_str()
+            .filter(|body| !body.trim().is_empty())
+            .map(|body| body.to_owned());
+    } else {
+        *update_status = "Program is up to date.".to_owned();
+    }
+
+    Ok(current_version.to_owned())
+}
+
+/// Returns the latest patch version known to Data Dragon, e.g. `13.14.1`.
+async fn data_dragon_version(client: &reqwest::Client) -> Result<String, Box<dyn Error>> {
+    let versions: Vec<String> = client
+        .get("https://ddragon.leagueoflegends.com/api/versions.json")
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    Ok(versions
+        .into_iter()
+        .next()
+        .ok_or("Data Dragon returned no versions")?)
+}
+
+/// Win rates per champion id and position, as served by the stats URL in the settings. Each
+/// champion maps `assignedPosition` names (and `"all"` for its overall win rate) to a win rate.
+type WinrateStats = HashMap<u32, HashMap<String, f32>>;
+
+async fn fetch_winrate_stats(url: &str) -> Result<WinrateStats, Box<dyn Error>> {
+    Ok(reqwest::get(url).await?.error_for_status()?.json().await?)
+}
+
+/// The picks tried on my current pick turn: the whole list as priorities, or only the slot of
+/// this turn when each pick turn has its own. Any number of picks works, down to a single one.
+fn pick_candidates(
+    champion_picks: &[(u32, String)],
+    pick_mode: PickMode,
+    pick_index: usize,
+) -> Vec<(u32, String)> {
+    match pick_mode {
+        PickMode::Priority => champion_picks.to_vec(),
+        PickMode::Separate => champion_picks
+            .get(pick_index)
+            .cloned()
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Sorts `candidates` from the highest to the lowest win rate for `position`, using the overall
+/// win rate when there's no position or no stats for it. Champions without any stats keep their
+/// priority order behind the others.
+fn order_by_winrate(
+    candidates: &mut [(u32, String)],
+    stats: &WinrateStats,
+    position: Option<&str>,
+) {
+    let winrate = |id: &u32| {
+        let rates = stats.get(id)?;
+        position
+            .and_then(|position| rates.get(position))
+            .or_else(|| rates.get("all"))
+            .copied()
+    };
+
+    candidates.sort_by(|(a, _), (b, _)| match (winrate(a), winrate(b)) {
+        (Some(a), Some(b)) => b.total_cmp(&a),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+/// Drops champions with id 0, which stands for "no pick" in the settings, and any champion whose
+/// id or name was already seen, so a malformed `champions.json` can't collide with the sentinel.
+fn validate_champions(champions: Vec<Champion>) -> Vec<Champion> {
+    let mut ids = HashSet::new();
+    let mut names = HashSet::new();
+    champions
+        .into_iter()
+        .filter(|champion| {
+            let valid = champion.id != 0
+                && ids.insert(champion.id)
+                && names.insert(clean_champion_name(&champion.name));
+            if !valid {
+                log_line(format!(
+                    "Dropping champion \"{}\" ({}): zero or duplicate id or name",
+                    champion.name, champion.id
+                ));
+            }
+            valid
+        })
+        .collect()
+}
+
+/// Downloads the champion list for the latest patch with names in `locale` (e.g. `ko_KR`) and
+/// saves it as `champions.json`, so name suggestions match what the client shows.
+async fn fetch_data_dragon_champions(locale: &str) -> Result<Vec<Champion>, Box<dyn Error>> {
+    let client = reqwest::Client::new();
+    let version = data_dragon_version(&client).await?;
+
+    let data: DataDragonChampions = client
+        .get(format!(
+            "https://ddragon.leagueoflegends.com/cdn/{}/data/{}/champion.json",
+            version, locale
+        ))
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    let mut champions = data
+        .data
+        .into_values()
+        .map(|champion| {
+            Ok(Champion {
+                id: champion.key.parse()?,
+                name: champion.name,
+                alias: champion.id,
+            })
+        })
+        .collect::<Result<Vec<Champion>, Box<dyn Error>>>()?;
+    champions.sort_by(|a, b| a.name.cmp(&b.name));
+    let champions = validate_champions(champions);
+
+    let champions_path = utils_path("champions.json");
+    if let Some(utils_dir) = champions_path.parent() {
+        std::fs::create_dir_all(utils_dir)?;
+    }
+    std::fs::write(&champions_path, serde_json::to_string_pretty(&champions)?)?;
+
+    Ok(champions)
+}
+
+/// Downloads the summoner spell list and the icons missing from the bundled set (Cleanse, Clarity,
+/// Mark, ...) for the latest patch from Data Dragon, and saves the list to `summoner_spells.json`.
+async fn fetch_data_dragon_spells(
+    summoner_spells: Arc<Mutex<Vec<SummonerSpell>>>,
+    spell_icons: Arc<Mutex<Vec<(String, Vec<u8>)>>>,
+    bundled_icons: Vec<String>,
+) -> Result<(), Box<dyn Error>> {
+    let client = reqwest::Client::new();
+    let version = data_dragon_version(&client).await?;
+
+    let summoners: DataDragonSummoners = client
+        .get(format!(
+            "https://ddragon.leagueoflegends.com/cdn/{}/data/en_US/summoner.json",
+            version
+        ))
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    let mut spells: Vec<SummonerSpell> = Vec::new();
+    for spell in summoners.data.into_values() {
+        if spell.id.contains("Placeholder") {
+            continue;
+        }
+
+        if !bundled_icons.contains(&spell.name)
+            && !spells.iter().any(|known| known.name == spell.name)
+        {
+            let icon = client
+                .get(format!(
+                    "https://ddragon.leagueoflegends.com/cdn/{}/img/spell/{}",
+                    version, spell.image.full
+                ))
+                .send()
+                .await?
+                .bytes()
+                .await?;
+            spell_icons
+                .lock()
+                .unwrap()
+                .push((spell.name.clone(), icon.to_vec()));
+        }
+
+        spells.push(SummonerSpell {
+            id: spell.id,
+            key: spell.key.parse()?,
+            name: spell.name,
+        });
+    }
+    spells.sort_by(|a, b| a.id.cmp(&b.id));
+
+    save_summoner_spells(&spells)?;
+    *summoner_spells.lock().unwrap() = spells;
+
+    Ok(())
+}
+
+fn save_summoner_spells(spells: &[SummonerSpell]) -> Result<(), Box<dyn Error>> {
+    let spells_path = utils_path("summoner_spells.json");
+    if let Some(utils_dir) = spells_path.parent() {
+        std::fs::create_dir_all(utils_dir)?;
+    }
+    std::fs::write(&spells_path, serde_json::to_string_pretty(spells)?)?;
+
+    Ok(())
+}
+
+fn save_plan_image(plan: &egui::ColorImage) -> Result<std::path::PathBuf, Box<dyn Error>> {
+    let plan_path = utils_path("plan.png");
+    if let Some(utils_dir) = plan_path.parent() {
+        std::fs::create_dir_all(utils_dir)?;
+    }
+    let pixels: Vec<u8> = plan
+        .pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_array())
+        .collect();
+    image::save_buffer(
+        &plan_path,
+        &pixels,
+        plan.size[0] as u32,
+        plan.size[1] as u32,
+        image::ColorType::Rgba8,
+    )?;
+
+    Ok(plan_path)
+}
+
+/// Returns whether the release tag `latest` (e.g. `v2.4.0`) is a newer version than `current`.
+/// Components are compared numerically, and a tag that isn't a version is never considered newer.
+fn is_outdated(current: &str, latest: &str) -> bool {
+    fn parse(version: &str) -> Option<Vec<u64>> {
+        version
+            .trim()
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect()
+    }
+
+    match (parse(current), parse(latest)) {
+        (Some(mut current), Some(mut latest)) => {
+            let len = current.len().max(latest.len());
+            current.resize(len, 0);
+            latest.resize(len, 0);
+            latest > current
+        }
+        _ => false,
+    }
+}
+
+fn hide_console_window() {
+    use std::ptr;
+    use winapi::um::wincon::GetConsoleWindow;
+    use winapi::um::winuser::{ShowWindow, SW_HIDE};
+
+    let window = unsafe { GetConsoleWindow() };
+    // https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-showwindow
+    if window != ptr::null_mut() {
+        unsafe {
+            ShowWindow(window, SW_HIDE);
+        }
+    }
+}
+
+/// Virtual key code of a kill switch key, letters and digits only. Their codes are the ASCII
+/// codes of the uppercase characters.
+fn hotkey_code(key: &str) -> Option<u32> {
+    let mut chars = key.trim().chars();
+    match (chars.next(), chars.next()) {
+        (Some(key), None) if key.is_ascii_alphanumeric() => Some(key.to_ascii_uppercase() as u32),
+        _ => None,
+    }
+}
+
+/// Watches the Ctrl+Shift kill switch hotkey on its own thread, the hotkey is registered system
+/// wide so it works without the window focused. Changes to `code` re-register it.
+fn run_kill_switch(code: Arc<AtomicU32>, on_press: impl Fn()) {
+    use std::ptr;
+    use winapi::um::winuser::{
+        PeekMessageW, RegisterHotKey, UnregisterHotKey, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, MSG,
+        PM_REMOVE, WM_HOTKEY,
+    };
+    const HOTKEY_ID: i32 = 1;
+
+    let mut registered = 0;
+    loop {
+        let wanted = code.load(Ordering::SeqCst);
+        if wanted != registered {
+            unsafe {
+                if registered != 0 {
+                    UnregisterHotKey(ptr::null_mut(), HOTKEY_ID);
                 }
-            });
+                let modifiers = (MOD_CONTROL | MOD_SHIFT | MOD_NOREPEAT) as u32;
+                if wanted != 0 && RegisterHotKey(ptr::null_mut(), HOTKEY_ID, modifiers, wanted) == 0
+                {
+                    log_line("Couldn't register the kill switch hotkey, it's taken");
+                }
+            }
+            registered = wanted;
+        }
+        // WM_HOTKEY is posted to the queue of the thread that registered the hotkey
+        let mut message: MSG = unsafe { std::mem::zeroed() };
+        while unsafe {
+            PeekMessageW(
+                &mut message,
+                ptr::null_mut(),
+                WM_HOTKEY,
+                WM_HOTKEY,
+                PM_REMOVE,
+            )
+        } != 0
+        {
+            on_press();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+}
+
+/// Opens a console for `--console`, release builds are started without one so logs written to
+/// stdout and stderr are otherwise lost.
+fn show_console_window() {
+    use winapi::um::consoleapi::AllocConsole;
+    use winapi::um::wincon::GetConsoleWindow;
+    use winapi::um::winuser::{ShowWindow, SW_SHOW};
+
+    let window = unsafe { GetConsoleWindow() };
+    unsafe {
+        if window.is_null() {
+            AllocConsole();
+        } else {
+            ShowWindow(window, SW_SHOW);
+        }
+    }
+}
+
+/// Writes a timestamped line to stderr and keeps it for the Logs window, dropping the oldest line
+/// past `LOG_CAPACITY`.
+fn log_line(line: impl AsRef<str>) {
+    let line = format!(
+        "{} {}",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        line.as_ref()
+    );
+    eprintln!("{}", line);
+    let mut logs = LOGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    logs.push_back(line);
+    while logs.len() > LOG_CAPACITY {
+        logs.pop_front();
+    }
+}
+
+/// How long the window may wait before redrawing on its own. Worker events wake it right away,
+/// this only keeps the ready check and champion select timers ticking and lets the GPU idle
+/// otherwise.
+fn repaint_interval(phase: Option<&str>, battery_saver: bool) -> std::time::Duration {
+    if matches!(phase, Some("ReadyCheck" | "ChampSelect")) {
+        std::time::Duration::from_millis(250)
+    } else if battery_saver {
+        std::time::Duration::from_secs(5)
+    } else {
+        std::time::Duration::from_secs(2)
+    }
+}
+
+/// Lays out my picks, bans and spells as the grid "Save as PNG" crops out, returning its area.
+fn plan_grid(
+    ui: &mut egui::Ui,
+    images: &mut ImageCache,
+    picks: &[(u32, String)],
+    bans: &[(u32, String)],
+    spells: &[Option<String>; 2],
+) -> egui::Rect {
+    let grid = egui::Grid::new("plan_grid").show(ui, |ui| {
+        ui.strong("Picks");
+        ui.vertical(|ui| {
+            for (idx, (_, name)) in picks.iter().enumerate() {
+                ui.label(format!("{}. {}", idx + 1, name));
+            }
+        });
+        ui.end_row();
+        ui.strong("Bans");
+        ui.vertical(|ui| {
+            if bans.is_empty() {
+                ui.label("None");
+            }
+            for (idx, (_, name)) in bans.iter().enumerate() {
+                ui.label(format!("{}. {}", idx + 1, name));
+            }
+        });
+        ui.end_row();
+        ui.strong("Spells");
+        ui.horizontal(|ui| {
+            for spell in spells.iter().flatten() {
+                if let Some(texture) = images.texture_id(spell, ui.ctx()) {
+                    ui.image(texture, egui::vec2(24.0, 24.0));
+                }
+            }
         });
+        ui.end_row();
+    });
+    grid.response.rect
+}
 
-        ctx.request_repaint_after(tokio::time::Duration::from_millis(500));
+/// Gives the active pick or ban box the keyboard back whenever nothing has it, so picks and bans
+/// can be typed one after another. Focusing any other widget ends this.
+fn keep_entry_focus(ui: &egui::Ui, input: &egui::Response, entry_focus: &mut bool) {
+    if !*entry_focus {
+        return;
     }
+    match ui.memory(|memory| memory.focus()) {
+        None => input.request_focus(),
+        Some(id) if id != input.id => *entry_focus = false,
+        Some(_) => {}
+    }
+}
 
-    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
-        std::process::exit(0);
+/// Adds a timestamped entry to the automation log shown in the Match State tab.
+fn log_action(action_log: &mut VecDeque<String>, action: impl AsRef<str>) {
+    action_log.push_back(format!(
+        "{} {}",
+        chrono::Local::now().format("%H:%M:%S"),
+        action.as_ref()
+    ));
+    while action_log.len() > ACTION_LOG_LENGTH {
+        action_log.pop_front();
     }
 }
 
-async fn update_checker(update_status: Arc<Mutex<String>>) -> Result<String, Box<dyn Error>> {
-    let repo_owner = "tacticaldeuce";
-    let repo_name = "circuit-watcher";
-    let url = format!(
-        "https://api.github.com/repos/{}/{}/releases/latest",
-        repo_owner, repo_name
-    );
+/// Writes the configuration, version, platform, recent actions, log lines and connection state
+/// into one JSON file on the desktop (or next to the executable without one) and returns its path.
+fn export_diagnostics(
+    settings: &Settings,
+    connection_status: Option<&str>,
+    gameflow_status: &str,
+    action_log: &VecDeque<String>,
+) -> Result<std::path::PathBuf, Box<dyn Error>> {
+    let logs: Vec<String> = LOGS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .cloned()
+        .collect();
+    let diagnostics = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "mock": MOCK_LCU.load(Ordering::SeqCst),
+        "connection_status": connection_status,
+        "gameflow_status": gameflow_status,
+        "recent_actions": action_log,
+        "log": logs,
+        "settings": settings,
+    });
 
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .header(
-            "User-Agent",
-            format!("CircuitWatcher/{} (Rust)", env!("CARGO_PKG_VERSION")),
-        )
+    let desktop = std::env::var_os("USERPROFILE")
+        .or_else(|| std::env::var_os("HOME"))
+        .map(|home| std::path::PathBuf::from(home).join("Desktop"))
+        .filter(|desktop| desktop.is_dir())
+        .unwrap_or_else(program_dir);
+    let path = desktop.join(format!(
+        "circuit-watcher-diagnostics-{}.json",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&path, serde_json::to_string_pretty(&diagnostics)?)?;
+
+    Ok(path)
+}
+
+/// Parses a 24-hour `HH:MM` time as typed in the settings.
+fn parse_clock_time(time: &str) -> Option<chrono::NaiveTime> {
+    chrono::NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()
+}
+
+/// Whether `now` falls into the do not disturb schedule from `start` to `end`, which may wrap
+/// past midnight. An invalid or empty schedule never pauses anything.
+fn in_quiet_hours(start: &str, end: &str, now: chrono::NaiveTime) -> bool {
+    let (Some(start), Some(end)) = (parse_clock_time(start), parse_clock_time(end)) else {
+        return false;
+    };
+
+    if start <= end {
+        start <= now && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Updates the worker's copy of a feature state, the UI is only told about changes.
+fn set_feature_state(
+    events: &mpsc::UnboundedSender<Event>,
+    states: &mut [FeatureState; 4],
+    feature: Feature,
+    state: FeatureState,
+) {
+    if states[feature as usize] != state {
+        states[feature as usize] = state;
+        let _ = events.send(Event::FeatureState(feature, state));
+    }
+}
+
+/// Reads the phase out of a `/lol-gameflow/v1/session` response. No session at all (404) and the
+/// `None` phase both mean the client is idle, any other error or a body without a phase is an error.
+fn gameflow_phase(status: u16, body: &serde_json::Value) -> Result<Option<&str>, String> {
+    if status == 404 {
+        return Ok(None);
+    }
+    match body["phase"].as_str() {
+        Some("None") if (200..300).contains(&status) => Ok(None),
+        Some(phase) if (200..300).contains(&status) => Ok(Some(phase)),
+        _ => Err(body["message"]
+            .as_str()
+            .map(|message| message.to_owned())
+            .unwrap_or_else(|| format!("unexpected gameflow response (HTTP {})", status))),
+    }
+}
+
+/// Whether a `/patcher/v1/products/league_of_legends/state` response shows the client updating or
+/// repairing the game.
+fn is_patching(state: &serde_json::Value) -> bool {
+    state["action"]
+        .as_str()
+        .map_or(false, |action| action != "Idle")
+        || state["isUpToDate"] == false
+}
+
+/// Summarizes the live client's `allgamedata` for the status line: game time, then the active
+/// player's KDA and creep score. `None` until the player shows up in `allPlayers`.
+fn live_game_summary(data: &serde_json::Value) -> Option<String> {
+    let active = &data["activePlayer"];
+    let name = active["riotId"]
+        .as_str()
+        .or_else(|| active["summonerName"].as_str())?;
+    let scores = &data["allPlayers"].as_array()?.iter().find(|player| {
+        player["riotId"].as_str() == Some(name) || player["summonerName"].as_str() == Some(name)
+    })?["scores"];
+    let game_time = data["gameData"]["gameTime"].as_f64().unwrap_or_default() as u64;
+
+    Some(format!(
+        "In game {}:{:02} — {}/{}/{}, {} CS",
+        game_time / 60,
+        game_time % 60,
+        scores["kills"].as_u64().unwrap_or_default(),
+        scores["deaths"].as_u64().unwrap_or_default(),
+        scores["assists"].as_u64().unwrap_or_default(),
+        scores["creepScore"].as_u64().unwrap_or_default()
+    ))
+}
+
+/// Whether an `all-grid-champions` entry can be picked on this account: owned, or free to play
+/// in this week's rotation.
+fn is_playable(champion: &serde_json::Value) -> bool {
+    champion["owned"] == true || champion["freeToPlay"] == true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Whether this account can lock a champion.
+enum Availability {
+    Owned,
+    FreeRotation,
+    Unowned,
+}
+
+/// Maps the champions of an `owned-champions-minimal` response to their availability, champions
+/// missing from it are unowned.
+fn champion_inventory(champions: &serde_json::Value) -> HashMap<u32, Availability> {
+    champions
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|champion| {
+            let id = champion["id"].as_u64().filter(|id| *id != 0)? as u32;
+            if champion["ownership"]["owned"] == true {
+                Some((id, Availability::Owned))
+            } else if champion["freeToPlay"] == true {
+                Some((id, Availability::FreeRotation))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Label of a pick suggestion: free rotation champions are marked and unowned ones greyed out.
+fn suggestion_text(label: String, availability: Option<Availability>) -> egui::RichText {
+    match availability {
+        Some(Availability::FreeRotation) => egui::RichText::new(format!("{} (free)", label)),
+        Some(Availability::Unowned) => egui::RichText::new(label).weak(),
+        Some(Availability::Owned) | None => egui::RichText::new(label),
+    }
+}
+
+/// Describes where my turn in champion select stands for the status line, so waiting for a turn
+/// can't be mistaken for the automation being stuck.
+fn turn_status(ban_pending: bool, ban_turn: bool, pick_turn: bool, locked: Option<&str>) -> String {
+    let state = match locked {
+        Some(champion) => format!("Locked {}", champion),
+        None if ban_pending && ban_turn => "Banning…".to_owned(),
+        None if ban_pending => "Waiting for ban turn".to_owned(),
+        None if pick_turn => "Picking…".to_owned(),
+        None => "Waiting for pick turn".to_owned(),
+    };
+    format!("Champion Selection — {}", state)
+}
+
+/// Sends a champion select action, sending it again up to `retries` times half a second apart
+/// while the client doesn't answer with a success status. Returns whether the client accepted it.
+async fn patch_action(
+    client: &reqwest::Client,
+    base_url: &str,
+    action_id: i32,
+    body: &serde_json::Value,
+    retries: u32,
+    events: &mpsc::UnboundedSender<Event>,
+) -> bool {
+    for attempt in 0..=retries {
+        if attempt > 0 {
+            report_action(
+                events,
+                format!(
+                    "Retrying {} ({}/{})",
+                    body["type"].as_str().unwrap_or("action"),
+                    attempt,
+                    retries
+                ),
+            );
+            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        }
+        let accepted = client
+            .patch(format!(
+                "{}/lol-champ-select/v1/session/actions/{}",
+                base_url, action_id
+            ))
+            .json(body)
+            .send()
+            .await
+            .map_or(false, |response| response.status().is_success());
+        if accepted {
+            return true;
+        }
+    }
+    false
+}
+
+/// Fetches the current champion select session, `None` if the request or its JSON failed.
+async fn fetch_session(client: &reqwest::Client, base_url: &str) -> Option<serde_json::Value> {
+    client
+        .get(format!("{}/lol-champ-select/v1/session", base_url))
+        .send()
+        .await
+        .ok()?
+        .json()
+        .await
+        .ok()
+}
+
+/// The champion I've locked or, before that, am hovering in a champion select session.
+fn my_champion(session: &serde_json::Value) -> Option<u32> {
+    let me = my_cell(session)?;
+    [&me["championId"], &me["championPickIntent"]]
+        .into_iter()
+        .filter_map(|id| id.as_u64())
+        .find(|id| *id != 0)
+        .map(|id| id as u32)
+}
+
+/// Whether one of my pick actions has completed with `champion_id`. My cell's `championId` is
+/// already set while the champion is only hovered, so it can't tell a lock apart.
+fn pick_locked(session: &serde_json::Value, champion_id: u32) -> bool {
+    let local_cell = &session["localPlayerCellId"];
+    session["actions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|turn| turn.as_array())
+        .flatten()
+        .any(|action| {
+            action["actorCellId"] == *local_cell
+                && action["type"] == "pick"
+                && action["completed"] == true
+                && action["championId"] == champion_id
+        })
+}
+
+fn my_cell(session: &serde_json::Value) -> Option<&serde_json::Value> {
+    let local_cell = &session["localPlayerCellId"];
+    session["myTeam"]
+        .as_array()?
+        .iter()
+        .find(|member| member["cellId"] == *local_cell)
+}
+
+/// The local player's entry in the session's `myTeam`, `None` while the team isn't populated yet.
+fn my_team_member(session: &serde_json::Value) -> Option<MyTeamData> {
+    let local_cell = session["localPlayerCellId"].as_u64()?;
+    serde_json::from_value::<Vec<MyTeamData>>(session["myTeam"].clone())
+        .ok()?
+        .into_iter()
+        .find(|data| data.cellId as u64 == local_cell)
+}
+
+/// Sends an entry for the automation log to the UI.
+fn report_action(events: &mpsc::UnboundedSender<Event>, action: impl Into<String>) {
+    let action = action.into();
+    log_line(&action);
+    let _ = events.send(Event::Action(action));
+}
+
+/// Appends a one-line summary of a finished game to `match_log.csv`, writing the header first if
+/// the file doesn't exist yet. `eog_stats` is the `/lol-end-of-game/v1/eog-stats-block` response.
+fn append_match_summary(
+    eog_stats: &serde_json::Value,
+    champions: &[Champion],
+    role: &str,
+) -> std::io::Result<()> {
+    let local_player = &eog_stats["localPlayer"];
+    let stats = &local_player["stats"];
+    let champion_id = local_player["championId"].as_u64().unwrap_or_default();
+    let champion = champions
+        .iter()
+        .find(|champion| champion.id as u64 == champion_id)
+        .map(|champion| champion.name.clone())
+        .unwrap_or_else(|| champion_id.to_string());
+    let result = if stats["WIN"].as_u64() == Some(1) {
+        "Win"
+    } else {
+        "Loss"
+    };
+
+    let log_path = utils_path("match_log.csv");
+    if let Some(utils_dir) = log_path.parent() {
+        std::fs::create_dir_all(utils_dir)?;
+    }
+    let write_header = !log_path.exists();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)?;
+
+    if write_header {
+        writeln!(file, "timestamp,champion,role,kills,deaths,assists,result")?;
+    }
+    writeln!(
+        file,
+        "{},{},{},{},{},{},{}",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        champion,
+        role,
+        stats["CHAMPIONS_KILLED"].as_u64().unwrap_or_default(),
+        stats["NUM_DEATHS"].as_u64().unwrap_or_default(),
+        stats["ASSISTS"].as_u64().unwrap_or_default(),
+        result
+    )
+}
+
+/// Parses a pasted rune page, either a JSON object in the client's perks-page format or a list of
+/// numbers: the primary style id, the secondary style id and then the nine selected perk ids.
+fn parse_rune_page(text: &str) -> Result<RunePage, String> {
+    const STYLES: [u32; 5] = [8000, 8100, 8200, 8300, 8400];
+
+    let page = if text.trim_start().starts_with('{') {
+        serde_json::from_str::<RunePage>(text).map_err(|_| "Invalid rune page JSON.".to_owned())?
+    } else {
+        let ids = text
+            .split(|c: char| c == ',' || c.is_whitespace() || c == '[' || c == ']')
+            .filter(|id| !id.is_empty())
+            .map(|id| id.parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|_| "Rune ids must be numbers.".to_owned())?;
+        if ids.len() < 2 {
+            return Err("Expected the two style ids followed by the perk ids.".to_owned());
+        }
+        RunePage {
+            name: default_rune_page_name(),
+            primaryStyleId: ids[0],
+            subStyleId: ids[1],
+            selectedPerkIds: ids[2..].to_vec(),
+        }
+    };
+
+    if !STYLES.contains(&page.primaryStyleId)
+        || !STYLES.contains(&page.subStyleId)
+        || page.primaryStyleId == page.subStyleId
+    {
+        return Err("Invalid primary or secondary rune style.".to_owned());
+    }
+    if page.selectedPerkIds.len() != 9 {
+        return Err(format!(
+            "A rune page needs 9 perks, got {}.",
+            page.selectedPerkIds.len()
+        ));
+    }
+
+    Ok(page)
+}
+
+/// Replaces the client's current rune page with `page`. The current page is deleted first when
+/// possible so the page limit isn't hit.
+async fn apply_rune_page(
+    rest_client: &reqwest::Client,
+    base_url: &str,
+    page: &RunePage,
+) -> Result<(), Box<dyn Error>> {
+    let current_page: serde_json::Value = rest_client
+        .get(format!("{}/lol-perks/v1/currentpage", base_url))
         .send()
+        .await?
+        .json()
         .await?;
-    let json = response.json::<serde_json::Value>().await?;
 
-    let latest_tag = json["tag_name"].as_str().unwrap();
+    if current_page["isDeletable"] == true {
+        rest_client
+            .delete(format!(
+                "{}/lol-perks/v1/pages/{}",
+                base_url, current_page["id"]
+            ))
+            .send()
+            .await?;
+    }
+
+    let mut body = serde_json::to_value(page)?;
+    body["current"] = true.into();
+    rest_client
+        .post(format!("{}/lol-perks/v1/pages", base_url))
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
 
-    let current_version = env!("CARGO_PKG_VERSION");
+    Ok(())
+}
 
-    let mut update_status = update_status.lock().unwrap();
+/// Sets my position preferences in the current lobby, e.g. `TOP` and `FILL`.
+async fn set_position_preferences(
+    rest_client: &reqwest::Client,
+    base_url: &str,
+    first: &str,
+    second: &str,
+) -> Result<(), reqwest::Error> {
+    rest_client
+        .put(format!(
+            "{}/lol-lobby/v2/lobby/members/localMember/position-preferences",
+            base_url
+        ))
+        .json(&serde_json::json!({
+            "firstPreference": first,
+            "secondPreference": second
+        }))
+        .send()
+        .await?
+        .error_for_status()?;
 
-    if !latest_tag.contains(current_version) {
-        *update_status =
-            format!("Program is outdated the latest version is {}", latest_tag).to_owned();
+    Ok(())
+}
+
+/// Creates a practice lobby on Summoner's Rift and starts it. With `cheats` it's a Practice Tool
+/// game, otherwise a normal custom game without the cheats panel.
+async fn start_practice_tool(
+    rest_client: &reqwest::Client,
+    base_url: &str,
+    cheats: bool,
+) -> Result<(), reqwest::Error> {
+    let lobby = serde_json::json!({
+        "customGameLobby": {
+            "configuration": {
+                "gameMode": if cheats { "PRACTICETOOL" } else { "CLASSIC" },
+                "gameMutator": "",
+                "gameServerRegion": "",
+                "mapId": 11,
+                "mutators": { "id": 1 },
+                "spectatorPolicy": "AllAllowed",
+                "teamSize": 5
+            },
+            "lobbyName": "Practice Tool",
+            "lobbyPassword": ""
+        },
+        "isCustom": true
+    });
+    rest_client
+        .post(format!("{}/lol-lobby/v2/lobby", base_url))
+        .json(&lobby)
+        .send()
+        .await?
+        .error_for_status()?;
+    rest_client
+        .post(format!(
+            "{}/lol-lobby/v1/lobby/custom/start-champ-select",
+            base_url
+        ))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Returns the pending lobby invitations, `None` if the request failed.
+async fn received_invitations(
+    rest_client: &reqwest::Client,
+    base_url: &str,
+) -> Option<Vec<Invitation>> {
+    let invitations: Vec<Invitation> = rest_client
+        .get(format!("{}/lol-lobby/v2/received-invitations", base_url))
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+
+    Some(
+        invitations
+            .into_iter()
+            .filter(|invitation| invitation.state == "Pending")
+            .collect(),
+    )
+}
+
+/// Returns the puuid and name of the summoner logged into the client. Riot IDs are shown as
+/// `name#tag`, older accounts fall back to their display name.
+async fn current_summoner(
+    rest_client: &reqwest::Client,
+    base_url: &str,
+) -> Option<(String, String)> {
+    let summoner: serde_json::Value = rest_client
+        .get(format!("{}/lol-summoner/v1/current-summoner", base_url))
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+
+    let puuid = summoner["puuid"].as_str()?.to_owned();
+    let name = match (summoner["gameName"].as_str(), summoner["tagLine"].as_str()) {
+        (Some(game_name), Some(tag_line)) if !game_name.is_empty() => {
+            format!("{}#{}", game_name, tag_line)
+        }
+        _ => summoner["displayName"].as_str()?.to_owned(),
+    };
+
+    Some((puuid, name))
+}
+
+/// Client region, op.gg region and u.gg platform for each server.
+const PROFILE_REGIONS: [(&str, &str, &str); 16] = [
+    ("BR", "br", "br1"),
+    ("EUNE", "eune", "eun1"),
+    ("EUW", "euw", "euw1"),
+    ("JP", "jp", "jp1"),
+    ("KR", "kr", "kr"),
+    ("LA1", "lan", "la1"),
+    ("LA2", "las", "la2"),
+    ("ME1", "me", "me1"),
+    ("NA", "na", "na1"),
+    ("OC1", "oce", "oc1"),
+    ("PH2", "ph", "ph2"),
+    ("RU", "ru", "ru"),
+    ("SG2", "sg", "sg2"),
+    ("TR", "tr", "tr1"),
+    ("TW2", "tw", "tw2"),
+    ("VN2", "vn", "vn2"),
+];
+
+/// Builds the op.gg and u.gg profile links for a Riot ID (`Name#TAG`) on `region`, `None` for a
+/// region neither site is known to use.
+fn profile_urls(region: &str, riot_id: &str) -> Option<(String, String)> {
+    let (_, op_gg_region, u_gg_platform) = PROFILE_REGIONS
+        .iter()
+        .find(|(client_region, _, _)| client_region.eq_ignore_ascii_case(region))?;
+    let name = riot_id.replace('#', "-").replace(' ', "%20");
+
+    Some((
+        format!("https://www.op.gg/summoners/{}/{}", op_gg_region, name),
+        format!(
+            "https://u.gg/lol/profile/{}/{}/overview",
+            u_gg_platform, name
+        ),
+    ))
+}
+
+/// Asks the client for its current gameflow phase and returns whether automation may send actions
+/// (only during ready check and champion select).
+async fn actions_allowed(rest_client: &reqwest::Client, base_url: &str) -> bool {
+    let phase: Option<String> = match rest_client
+        .get(format!("{}/lol-gameflow/v1/gameflow-phase", base_url))
+        .send()
+        .await
+    {
+        Ok(response) => response.json().await.ok(),
+        Err(_) => None,
+    };
+
+    matches!(phase.as_deref(), Some("ReadyCheck") | Some("ChampSelect"))
+}
+
+/// Address and credentials of the League Client API, read from its lockfile.
+struct LcuConnection {
+    base_url: String,
+    b64_auth: String,
+}
+
+/// Reads the League Client lockfile, or points at the in-process mock client when running with
+/// `--mock`.
+fn lcu_connection() -> Option<LcuConnection> {
+    if MOCK_LCU.load(Ordering::SeqCst) {
+        return Some(LcuConnection {
+            base_url: format!("http://127.0.0.1:{}", MOCK_LCU_PORT),
+            b64_auth: String::new(),
+        });
+    }
+
+    let lockfile = LeagueClientConnector::parse_raw_info().ok()?;
+    Some(LcuConnection {
+        base_url: format!("https://127.0.0.1:{}", lockfile.port),
+        b64_auth: lockfile.b64_auth,
+    })
+}
+
+/// Returns the fixture of the scenario requested with `--mock[=<scenario>]` or the
+/// `CIRCUIT_WATCHER_MOCK` environment variable. A bare `--mock` plays the draft pick scenario.
+fn mock_scenario() -> Option<&'static str> {
+    let requested = std::env::args()
+        .find_map(|arg| match arg.strip_prefix("--mock") {
+            Some("") => Some("draft".to_owned()),
+            Some(scenario) => scenario.strip_prefix('=').map(str::to_owned),
+            None => None,
+        })
+        .or_else(|| std::env::var("CIRCUIT_WATCHER_MOCK").ok())?;
+
+    let fixture = MOCK_SCENARIOS
+        .iter()
+        .find(|(name, _)| *name == requested)
+        .map(|(_, fixture)| *fixture);
+    if fixture.is_none() {
+        log_line(format!(
+            "Unknown mock scenario \"{}\", using the League Client",
+            requested
+        ));
+    }
+    fixture
+}
+
+/// Serves a mock scenario in place of the League Client so the UI and automation can be exercised
+/// without League running.
+async fn run_mock_lcu(fixture: &'static str) -> std::io::Result<()> {
+    let routes: Arc<HashMap<String, serde_json::Value>> = Arc::new(serde_json::from_str(fixture)?);
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", MOCK_LCU_PORT)).await?;
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let routes = Arc::clone(&routes);
+        tokio::spawn(async move {
+            let _ = serve_mock_request(stream, &routes).await;
+        });
+    }
+}
+
+async fn serve_mock_request(
+    mut stream: tokio::net::TcpStream,
+    routes: &HashMap<String, serde_json::Value>,
+) -> std::io::Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Read until the headers and the body they announce have arrived.
+    let mut request = Vec::new();
+    let mut buffer = [0; 4096];
+    loop {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+
+        if let Some(header_end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            let headers = String::from_utf8_lossy(&request[..header_end]);
+            let content_length = headers
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if request.len() >= header_end + 4 + content_length {
+                break;
+            }
+        }
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.split_whitespace();
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line.next().unwrap_or_default();
+    if method != "GET" {
+        log_line(format!("Mock LeagueClient: {} {}", method, path));
+    }
+
+    let (status, body) = mock_response(routes, method, path);
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await
+}
+
+/// Picks the mock response for a request. GETs are looked up by their exact path and then by the
+/// path with its last segment replaced by `*`; every other method succeeds without a body.
+fn mock_response(
+    routes: &HashMap<String, serde_json::Value>,
+    method: &str,
+    path: &str,
+) -> (&'static str, String) {
+    if method != "GET" {
+        return ("204 No Content", String::new());
+    }
+
+    let wildcard = path
+        .rsplit_once('/')
+        .map(|(parent, _)| format!("{}/*", parent));
+    match routes
+        .get(path)
+        .or_else(|| wildcard.and_then(|wildcard| routes.get(&wildcard)))
+    {
+        Some(body) => ("200 OK", body.to_string()),
+        None => (
+            "404 Not Found",
+            serde_json::json!({ "errorCode": "RPC_ERROR", "httpStatus": 404 }).to_string(),
+        ),
+    }
+}
+
+/// Builds the client used for every League Client request, authenticated with the lockfile's
+/// `b64_auth` and trusting Riot's self-signed certificate. Failures are logged and returned.
+fn build_rest_client(
+    b64_auth: &str,
+    cert: &reqwest::Certificate,
+) -> Result<reqwest::Client, String> {
+    let mut headers = header::HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(format!("Basic {}", b64_auth).as_str())
+            .map_err(|error| error.to_string())?,
+    );
+    ClientBuilder::new()
+        .add_root_certificate(cert.clone())
+        .default_headers(headers)
+        .build()
+        .map_err(|error| {
+            log_line(format!("Couldn't build the client: {}", error));
+            error.to_string()
+        })
+}
+
+/// Re-reads the lockfile and rebuilds the client for it, forgetting the summoner of the previous
+/// connection. Returns whether a client was found.
+fn reconnect(
+    lc_info: &mut LcuConnection,
+    rest_client: &mut reqwest::Client,
+    summoner: &mut Option<(String, String)>,
+    cert: &reqwest::Certificate,
+    events: &mpsc::UnboundedSender<Event>,
+) -> bool {
+    let Some(connection) = lcu_connection() else {
+        return false;
+    };
+    *lc_info = connection;
+    *summoner = None;
+    let _ = events.send(Event::Summoner(None));
+    match build_rest_client(&lc_info.b64_auth, cert) {
+        Ok(client) => *rest_client = client,
+        Err(error) => {
+            let _ = events.send(Event::Status(format!(
+                "Couldn't connect to the client: {}",
+                error
+            )));
+        }
+    }
+    true
+}
+
+/// The watchlist as it's typed into its input, so the saved list can be edited.
+fn watchlist_text(counter_bans: &[(u32, String)]) -> String {
+    counter_bans
+        .iter()
+        .map(|(_, name)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Normalizes a champion name for matching against user input: case, spaces, apostrophes and
+/// periods are ignored so "kaisa" matches "Kai'Sa" and "drmundo" matches "Dr. Mundo".
+fn clean_champion_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '\'' | '.'))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// The draft positions and the names shown for them.
+const ROLES: [(&str, &str); 5] = [
+    ("top", "Top"),
+    ("jungle", "Jungle"),
+    ("middle", "Mid"),
+    ("bottom", "Bot"),
+    ("utility", "Support"),
+];
+
+/// Other spellings of the `assignedPosition` values seen across modes, mapped to the usual ones.
+const POSITION_ALIASES: [(&str, &str); 8] = [
+    ("jungler", "jungle"),
+    ("jg", "jungle"),
+    ("mid", "middle"),
+    ("bot", "bottom"),
+    ("adc", "bottom"),
+    ("support", "utility"),
+    ("supp", "utility"),
+    ("sup", "utility"),
+];
+
+/// Normalizes an `assignedPosition` value to the lowercase names used by the client in draft
+/// (`top`, `jungle`, `middle`, `bottom`, `utility`). Empty and `NONE` positions are `None`.
+fn normalize_position(position: &str) -> Option<String> {
+    let position = position.trim().to_lowercase();
+    if position.is_empty() || position == "none" {
+        return None;
+    }
+    let position = POSITION_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == position)
+        .map_or(position.clone(), |(_, canonical)| canonical.to_string());
+    Some(position)
+}
+
+/// Display name of a lobby position preference (`TOP`, `FILL`, `UNSELECTED`, ...).
+fn preference_label(preference: &str) -> &'static str {
+    match normalize_position(preference).as_deref() {
+        Some("fill") => "Fill",
+        Some(position) => ROLES
+            .iter()
+            .find(|(role, _)| *role == position)
+            .map_or("None", |(_, label)| label),
+        None => "None",
+    }
+}
+
+/// Whether the assigned `position` (as returned by `normalize_position`) is neither of the
+/// positions chosen in the lobby. Fill and unselected preferences never match a position.
+fn is_autofilled(position: &str, preferences: &[String]) -> bool {
+    !preferences
+        .iter()
+        .any(|preference| normalize_position(preference).as_deref() == Some(position))
+}
+
+/// Returns the conventional summoner spell pair for an `assignedPosition` value from the
+/// champion select session, or `None` if the position has no meaningful default.
+fn suggested_spells(position: &str) -> Option<(&'static str, &'static str)> {
+    match position {
+        "top" => Some(("Flash", "Teleport")),
+        "jungle" => Some(("Flash", "Smite")),
+        "middle" => Some(("Flash", "Ignite")),
+        "bottom" => Some(("Flash", "Heal")),
+        "utility" => Some(("Flash", "Ignite")),
+        _ => None,
+    }
+}
+
+/// Makes sure a jungler's spell pair has Smite. A configured Flash or Ghost stays as the partner
+/// spell in its slot and Smite replaces the other spell. With both configured Flash is kept, with
+/// neither Smite goes first.
+fn jungle_spells(spell1: &str, spell2: &str) -> (String, String) {
+    if spell1 == "Smite" || spell2 == "Smite" {
+        return (spell1.to_owned(), spell2.to_owned());
+    }
+    let is_partner = |spell: &str| matches!(spell, "Flash" | "Ghost");
+    let keep_first = match (is_partner(spell1), is_partner(spell2)) {
+        (true, true) => spell1 == "Flash",
+        (first, _) => first,
+    };
+    if keep_first {
+        (spell1.to_owned(), "Smite".to_owned())
     } else {
-        *update_status = "Program is up to date.".to_owned();
+        ("Smite".to_owned(), spell2.to_owned())
     }
+}
 
-    Ok(current_version.to_owned())
+/// Returns the directory the executable lives in, falling back to the current working directory.
+/// Shortcuts commonly launch the program with a different working directory.
+fn program_dir() -> std::path::PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
 }
 
-fn hide_console_window() {
-    use std::ptr;
-    use winapi::um::wincon::GetConsoleWindow;
-    use winapi::um::winuser::{ShowWindow, SW_HIDE};
+/// Returns the folder settings and champion data are kept in: `--data-dir=<path>`, else the
+/// `CIRCUIT_WATCHER_DATA` environment variable, else the `utils` folder next to the executable.
+fn data_dir() -> std::path::PathBuf {
+    std::env::args()
+        .find_map(|arg| arg.strip_prefix("--data-dir=").map(str::to_owned))
+        .or_else(|| std::env::var("CIRCUIT_WATCHER_DATA").ok())
+        .filter(|dir| !dir.trim().is_empty())
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| program_dir().join("utils"))
+}
+
+fn utils_path(file_name: &str) -> std::path::PathBuf {
+    data_dir().join(file_name)
+}
+
+/// Reads `file_name` from the data folder, see `data_dir`. If the file is missing the
+/// copy embedded at compile time is used instead so startup never depends on the folder existing.
+fn read_utils_file(file_name: &str, fallback: &str) -> String {
+    std::fs::read_to_string(utils_path(file_name)).unwrap_or_else(|_| fallback.to_owned())
+}
 
-    let window = unsafe { GetConsoleWindow() };
-    // https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-showwindow
-    if window != ptr::null_mut() {
-        unsafe {
-            ShowWindow(window, SW_HIDE);
-        }
-    }
+/// Parses a JSON file from the data folder, falling back to the bundled copy when it's broken.
+/// Anything that can't be parsed is added to `problems` and leaves the default value.
+fn load_utils_json<T: serde::de::DeserializeOwned + Default>(
+    file_name: &str,
+    bundled: &str,
+    problems: &mut Vec<String>,
+) -> T {
+    serde_json::from_str(&read_utils_file(file_name, bundled))
+        .or_else(|error| {
+            problems.push(format!(
+                "{} couldn't be read ({}), the bundled copy is used.",
+                file_name, error
+            ));
+            serde_json::from_str(bundled)
+        })
+        .unwrap_or_else(|error| {
+            problems.push(format!("The bundled {} is broken: {}", file_name, error));
+            T::default()
+        })
 }
 
-fn image_loader(img_name: &str, img_bytes: &[u8]) -> (String, RetainedImage) {
-    (
-        img_name.to_string(),
-        RetainedImage::from_image_bytes(img_name, img_bytes).unwrap(),
-    )
+fn image_loader(
+    img_name: &str,
+    img_bytes: &[u8],
+    problems: &mut Vec<String>,
+) -> Option<RetainedImage> {
+    RetainedImage::from_image_bytes(img_name, img_bytes)
+        .map_err(|error| {
+            problems.push(format!(
+                "The {} icon couldn't be decoded: {}",
+                img_name, error
+            ))
+        })
+        .ok()
+}
+
+/// Decodes the bundled window and taskbar icon.
+fn app_icon() -> Option<eframe::IconData> {
+    let image =
+        egui_extras::image::load_image_bytes(include_bytes!("../utils/images/icon.png")).ok()?;
+
+    Some(eframe::IconData {
+        rgba: image
+            .pixels
+            .iter()
+            .flat_map(|pixel| pixel.to_srgba_unmultiplied())
+            .collect(),
+        width: image.size[0] as u32,
+        height: image.size[1] as u32,
+    })
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    let console = std::env::args().any(|arg| arg == "--console");
+    if console {
+        show_console_window();
+    }
+    // Panics caught per automation poll still end up in the log with what went wrong and where.
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic");
+        match info.location() {
+            Some(location) => log_line(format!("Panic at {}: {}", location, payload)),
+            None => log_line(format!("Panic: {}", payload)),
+        }
+        default_hook(info);
+    }));
+    if let Some(fixture) = mock_scenario() {
+        MOCK_LCU.store(true, Ordering::SeqCst);
+        tokio::spawn(run_mock_lcu(fixture));
+    }
+
+    let (command_sender, mut commands) = mpsc::unbounded_channel();
+    // The worker's events go through a relay that wakes the window for each one, it has no
+    // egui context until the window exists.
+    let (events, mut worker_events) = mpsc::unbounded_channel();
+    let (ui_events, event_receiver) = mpsc::unbounded_channel();
+    let app = GUI::new(command_sender, event_receiver);
+
     let options = eframe::NativeOptions {
-        // icon_data: None,
+        icon_data: app_icon(),
+        always_on_top: app.always_on_top,
         min_window_size: Some(vec2(330.0, 320.0)),
         initial_window_size: Some(egui::vec2(500.0, 400.0)),
+        initial_window_pos: app
+            .window_position
+            .filter(|_| app.remember_window_position)
+            .map(|(x, y)| egui::pos2(x, y)),
         ..Default::default()
     };
 
-    let app = GUI::new();
-
-    let champion_picks_clone = Arc::clone(&app.champion_picks);
-    let ban_picks_clone = Arc::clone(&app.ban_picks);
     let connection_status = Arc::clone(&app.connection_status);
     let connection_status_clone = Arc::clone(&app.connection_status);
-    let gameflow_status = Arc::clone(&app.gameflow_status);
-    let pick_ban_selection_clone = Arc::clone(&app.pick_ban_selection);
-    let rune_page_change_clone = Arc::clone(&app.rune_page_selection);
-    let auto_accept_clone = Arc::clone(&app.auto_accept);
+    let mut champions_clone = app.champions.clone();
+    let mut settings = app.saved_settings.clone();
     let update_status_clone = Arc::clone(&app.update_status);
     let current_version_clone = Arc::clone(&app.current_version);
+    let release_notes_clone = Arc::clone(&app.release_notes);
     let update_clone = Arc::clone(&app.update);
     let asset_name_clone = Arc::clone(&app.asset_name);
-    let selected_image1_clone = Arc::clone(&app.selected_image1);
-    let selected_image2_clone = Arc::clone(&app.selected_image2);
-    let spell_selection_clone = Arc::clone(&app.spell_selection);
-    let assigned_role_clone = Arc::clone(&app.assigned_role);
+    let download_status_clone = Arc::clone(&app.update_status);
+    let mut summoner_spells = app.sent_summoner_spells.clone();
+    let data_dragon_spells = Arc::clone(&app.summoner_spells);
+    let data_dragon_spell_icons = Arc::clone(&app.downloaded_spell_icons);
+    let bundled_spell_icons: Vec<String> = app.images.names();
+
+    let data_dragon_locale = Arc::clone(&app.champion_locale);
+    let data_dragon_champions = Arc::clone(&app.downloaded_champions);
+    let data_dragon_refresh = Arc::clone(&app.refresh_champions);
+
+    // The bundled icons stay as the offline fallback if Data Dragon can't be reached.
+    tokio::spawn(async move {
+        let _ = fetch_data_dragon_spells(
+            data_dragon_spells,
+            data_dragon_spell_icons,
+            bundled_spell_icons,
+        )
+        .await;
+    });
 
+    // Keeps the champion names in the language chosen in the settings, re-fetching on change or
+    // when asked to because the list is out of date.
     tokio::spawn(async move {
+        let mut fetched_locale = String::new();
         loop {
-            hide_console_window();
+            let locale = data_dragon_locale.lock().unwrap().clone();
+            if locale != fetched_locale || data_dragon_refresh.swap(false, Ordering::SeqCst) {
+                if let Ok(champions) = fetch_data_dragon_champions(&locale).await {
+                    *data_dragon_champions.lock().unwrap() = Some(champions);
+                }
+                fetched_locale = locale;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        }
+    });
+
+    tokio::spawn(async move {
+        loop {
+            if !console {
+                hide_console_window();
+            }
             let update = update_clone.load(Ordering::SeqCst);
             let asset_name = Arc::clone(&asset_name_clone);
 
@@ -848,32 +5071,43 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 let body: serde_json::Value = response.json().await.unwrap();
                 let release: Release = serde_json::from_value(body).unwrap();
 
-                if status.is_success() {
+                // Without anything to download the update flag is cleared, otherwise the release
+                // would be requested again on every pass.
+                if !status.is_success() || release.assets.is_empty() {
+                    log_line(format!(
+                        "No update to download (HTTP {}, {} assets)",
+                        status,
+                        release.assets.len()
+                    ));
+                    *download_status_clone.lock().unwrap() =
+                        "The latest release has no download yet, try again later.".to_owned();
+                    update_clone.store(false, Ordering::SeqCst);
+                } else {
                     for asset in release.assets {
                         let asset_url = asset.browser_download_url.clone();
 
                         let response = client.get(&asset_url).send().await.unwrap();
 
-                        let file_name = asset.name.clone();
+                        let file_name = program_dir().join(&asset.name);
                         let mut file = std::fs::File::create(&file_name).unwrap();
                         let contents = response.bytes().await.unwrap();
 
                         file.write_all(&contents).unwrap();
 
-                        *asset_name.lock().unwrap() = asset.name.clone();
+                        *asset_name.lock().unwrap() = file_name.to_string_lossy().into_owned();
                         update_clone.store(false, Ordering::SeqCst);
                     }
                 }
             }
-            match LeagueClientConnector::parse_raw_info() {
-                Ok(lockfile) => {
+            match lcu_connection() {
+                Some(connection) => {
                     let mut status = connection_status.lock().unwrap();
                     *status = Some(format!(
-                        "Connected to LeagueClient on https://127.0.0.1:{}",
-                        lockfile.port
+                        "Connected to LeagueClient on {}",
+                        connection.base_url
                     ));
                 }
-                Err(_) => {
+                None => {
                     let mut status = connection_status.lock().unwrap();
                     *status = Some("LeagueClient not found, may be closed.".to_owned());
                 }
@@ -885,7 +5119,12 @@ async fn main() -> Result<(), Box<dyn Error>> {
         let status = connection_status_clone.lock().unwrap().clone();
         let current_version_clone = Arc::clone(&current_version_clone);
 
-        *current_version_clone.lock().unwrap() = update_checker(update_status_clone).await.unwrap();
+        // GitHub being unreachable doesn't matter for a client running locally, the version is
+        // known anyway.
+        match update_checker(Arc::clone(&update_status_clone), release_notes_clone).await {
+            Ok(version) => *current_version_clone.lock().unwrap() = version,
+            Err(_) => *update_status_clone.lock().unwrap() = OFFLINE_STATUS.to_owned(),
+        }
 
         // Both of this while loops are to ensure there is a viable connection to the League Client
         while status.is_none() {
@@ -906,467 +5145,2149 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
         tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
 
-        let mut lc_info = LeagueClientConnector::parse_raw_info().unwrap();
-        let mut auth_header =
-            HeaderValue::from_str(format!("Basic {}", lc_info.b64_auth).as_str()).unwrap();
-        let cert =
-            reqwest::Certificate::from_pem(include_bytes!("../utils/riotgames.pem")).unwrap();
-        let mut headers = header::HeaderMap::new();
-
-        headers.insert(AUTHORIZATION, auth_header.clone());
-        let mut rest_client = ClientBuilder::new()
+        let mut lc_info = lcu_connection().unwrap();
+        // also shown in the startup problems window, the worker can't run without it
+        let cert = match reqwest::Certificate::from_pem(RIOT_CERT) {
+            Ok(cert) => cert,
+            Err(error) => {
+                log_line(format!("Couldn't load the Riot certificate: {}", error));
+                return;
+            }
+        };
+        let mut rest_client = loop {
+            match build_rest_client(&lc_info.b64_auth, &cert) {
+                Ok(client) => break client,
+                Err(error) => {
+                    let _ = events.send(Event::Status(format!(
+                        "Couldn't connect to the client: {}",
+                        error
+                    )));
+                    tokio::time::sleep(tokio::time::Duration::from_secs(4)).await;
+                    if let Some(connection) = lcu_connection() {
+                        lc_info = connection;
+                    }
+                }
+            }
+        };
+        // only needed for the live game stats, which are skipped without it
+        let live_client = ClientBuilder::new()
             .add_root_certificate(cert.clone())
-            .default_headers(headers)
             .build()
-            .unwrap();
-
-        let spells_data =
-            std::fs::read_to_string("./utils/summoner_spells.json").expect("Failed to read file");
-        let summoner_spells: Vec<SummonerSpell> =
-            serde_json::from_str(&spells_data).expect("Failed to parse JSON");
+            .ok();
 
         let mut locked_champ = false;
+        let mut fallback_fired = false;
+        let mut last_spell_error: Option<String> = None;
+        let mut last_pick_id: Option<i32> = None;
+        let mut failed_requests = 0;
+        let mut last_phase: Option<String> = None;
+        // The phase last sent to the UI, cleared while no client is connected.
+        let mut sent_phase: Option<String> = None;
+        let mut assigned_position: Option<String> = None;
+        let mut summoner: Option<(String, String)> = None;
+        let mut capture_rune_page = false;
+        let mut grid_champions: Vec<serde_json::Value> = Vec::new();
+        let mut grid_fetched: Option<std::time::Instant> = None;
+        // ban action and champion last hovered, a different champion for the same action is
+        // hovered again
+        let mut hovered_ban: Option<(i32, u32)> = None;
+        let mut outdated_reported = false;
+        // patch and URL the stats were fetched for, so they're only downloaded once per patch
+        let mut winrate_stats: Option<(String, String, WinrateStats)> = None;
+        let mut winrate_checked_for: Option<String> = None;
+        let mut data_dragon_patch: Option<(std::time::Instant, String)> = None;
+        let mut feature_states = [FeatureState::Idle; 4];
+        let mut smite_checked = false;
+        let mut ban_conflict_reported = false;
+        // positions picked in the lobby, to tell an autofill apart in champion select
+        let mut position_preferences: Vec<String> = Vec::new();
+        let mut autofill_reported = false;
+        let mut practice_tool: Option<bool> = None;
+        let mut client_patching = false;
+        let mut champion_icons_sent: HashSet<u32> = HashSet::new();
+        // the last session and latency sent to the UI, both are only sent again once they change
+        let mut last_session: Option<serde_json::Value> = None;
+        let mut last_latency: Option<std::time::Duration> = None;
+        // Per-queue automation is ignored while set, it would otherwise override the kill switch.
+        let mut kill_switch = false;
+        let mut position_request: Option<(String, String)> = None;
+        // the spell pair last sent successfully in this champion select
+        let mut spells_set: Option<(String, String)> = None;
+        let mut pick_now = false;
+        let mut ban_now = false;
+        let mut invitations: Vec<Invitation> = Vec::new();
+        let mut invitation_answers: Vec<(String, bool)> = Vec::new();
         loop {
-            if connection_status_clone
-                .lock()
-                .unwrap()
-                .clone()
-                .as_ref()
-                .unwrap()
-                .contains("LeagueClient not found, may be closed.")
-            {
-                match LeagueClientConnector::parse_raw_info() {
-                    Ok(riotlockfile) => {
-                        lc_info = riotlockfile;
-                        auth_header =
-                            HeaderValue::from_str(format!("Basic {}", lc_info.b64_auth).as_str())
-                                .unwrap();
-                        headers = header::HeaderMap::new();
-
-                        headers.insert(AUTHORIZATION, auth_header.clone());
-                        rest_client = ClientBuilder::new()
-                            .add_root_certificate(cert.clone())
-                            .default_headers(headers)
-                            .build()
-                            .unwrap();
-
+            // A single bad poll (e.g. an unexpected JSON shape) is logged by the panic hook and
+            // skipped instead of stopping the automation for the rest of the session. `return`
+            // ends the poll early.
+            let poll = async {
+                if connection_status_clone
+                    .lock()
+                    .unwrap()
+                    .clone()
+                    .as_ref()
+                    .unwrap()
+                    .contains("LeagueClient not found, may be closed.")
+                {
+                    if reconnect(
+                        &mut lc_info,
+                        &mut rest_client,
+                        &mut summoner,
+                        &cert,
+                        &events,
+                    ) {
                         tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+                    } else {
+                        if sent_phase.take().is_some() {
+                            let _ = events.send(Event::Phase(None));
+                        }
+                        return;
                     }
-                    Err(_) => {
-                        continue;
+                    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                }
+
+                while let Ok(command) = commands.try_recv() {
+                    match command {
+                        Command::Champions(champions) => champions_clone = champions,
+                        Command::SummonerSpells(spells) => summoner_spells = spells,
+                        Command::ApplySettings(new_settings) => {
+                            settings = new_settings;
+                            // turning any automation back on releases the kill switch
+                            if settings.auto_accept
+                                || settings.pick_ban_selection
+                                || settings.spell_selection
+                                || settings.rune_page_selection
+                            {
+                                kill_switch = false;
+                            }
+                        }
+                        Command::CaptureRunePage => capture_rune_page = true,
+                        Command::PickNow => pick_now = true,
+                        Command::BanNow => ban_now = true,
+                        Command::AnswerInvitation(id, accept) => {
+                            invitation_answers.push((id, accept))
+                        }
+                        Command::StartPracticeTool(cheats) => practice_tool = Some(cheats),
+                        Command::KillSwitch => {
+                            kill_switch = true;
+                            settings.auto_accept = false;
+                            settings.pick_ban_selection = false;
+                            settings.spell_selection = false;
+                            settings.rune_page_selection = false;
+                            report_action(&events, "Kill switch: automation disabled");
+                        }
+                        Command::SetPositionPreferences(first, second) => {
+                            position_request = Some((first, second))
+                        }
                     }
                 }
-                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-            }
 
-            let champion_picks = champion_picks_clone.lock().unwrap().clone();
-            let ban_picks = ban_picks_clone.lock().unwrap().clone();
-            let gameflow_status_clone = Arc::clone(&gameflow_status);
-            let pick_ban_selection = pick_ban_selection_clone.load(Ordering::SeqCst);
-            let rune_change = rune_page_change_clone.load(Ordering::SeqCst);
-            let auto_accept = auto_accept_clone.load(Ordering::SeqCst);
-            let spell1 = Arc::clone(&selected_image1_clone);
-            let spell2 = Arc::clone(&selected_image2_clone);
-            let spell_selection = spell_selection_clone.load(Ordering::SeqCst);
-            let assigned_position = Arc::clone(&assigned_role_clone);
-
-            let gameflow: serde_json::Value = rest_client
-                .get(format!(
-                    "https://127.0.0.1:{}/lol-gameflow/v1/session",
-                    lc_info.port
-                ))
-                .send()
-                .await
-                .unwrap()
-                .json()
-                .await
-                .unwrap();
-            let phase = gameflow["phase"].as_str();
-
-            match phase {
-                Some("Matchmaking") => {
-                    *assigned_position.lock().unwrap() = None;
-                    *gameflow_status_clone.lock().unwrap() = "Looking for a match".to_owned();
-                    locked_champ = false;
+                if let Some((first, second)) = position_request.take() {
+                    match set_position_preferences(&rest_client, &lc_info.base_url, &first, &second)
+                        .await
+                    {
+                        Ok(()) => report_action(
+                            &events,
+                            format!(
+                                "Set roles to {} and {}",
+                                preference_label(&first),
+                                preference_label(&second)
+                            ),
+                        ),
+                        Err(error) => {
+                            report_action(&events, format!("Couldn't set roles: {}", error))
+                        }
+                    }
+                }
+
+                if let Some(cheats) = practice_tool.take() {
+                    match start_practice_tool(&rest_client, &lc_info.base_url, cheats).await {
+                        Ok(()) => report_action(&events, "Started Practice Tool"),
+                        Err(error) => report_action(
+                            &events,
+                            format!("Couldn't start Practice Tool: {}", error),
+                        ),
+                    }
+                }
+
+                let champion_picks = settings.champion_picks.clone();
+                // a skipped ban is never touched, the client skips it once the turn runs out
+                let ban_picks = if settings.skip_ban {
+                    Vec::new()
+                } else {
+                    settings.ban_picks.clone()
+                };
+                let pick_ban_selection = settings.pick_ban_selection;
+                let rune_change = settings.rune_page_selection;
+                let pick_fallback = settings.pick_fallback;
+                let secondary_picks = settings.secondary_picks.clone();
+                // the kill switch stops answering trades and swaps as well
+                let (trade_behavior, swap_behavior) = if kill_switch {
+                    (TradeBehavior::Off, SwapBehavior::Off)
+                } else {
+                    (settings.trade_behavior, settings.swap_behavior)
+                };
+                let hover_ban = settings.hover_ban;
+                let decoy_ban = settings.decoy_ban.clone().filter(|_| settings.decoy_hover);
+                let action_retries = settings.action_retries;
+                let counter_bans = if settings.counter_ban && !settings.skip_ban {
+                    settings.counter_bans.clone()
+                } else {
+                    Vec::new()
+                };
+                let rune_pages = settings.rune_pages.clone();
+
+                if summoner.is_none() {
+                    summoner = current_summoner(&rest_client, &lc_info.base_url).await;
+                    if summoner.is_some() {
+                        let _ = events.send(Event::Summoner(summoner.clone()));
+                        if let Ok(response) = rest_client
+                            .get(format!(
+                                "{}/lol-champions/v1/owned-champions-minimal",
+                                lc_info.base_url
+                            ))
+                            .send()
+                            .await
+                        {
+                            if let Ok(champions) = response.json::<serde_json::Value>().await {
+                                let _ = events
+                                    .send(Event::ChampionInventory(champion_inventory(&champions)));
+                            }
+                        }
+                        if let Ok(response) = rest_client
+                            .get(format!("{}/riotclient/region-locale", lc_info.base_url))
+                            .send()
+                            .await
+                        {
+                            if let Ok(locale) = response.json::<serde_json::Value>().await {
+                                if let Some(region) = locale["region"].as_str() {
+                                    let _ = events.send(Event::Region(region.to_owned()));
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if std::mem::take(&mut capture_rune_page) {
+                    if let Ok(response) = rest_client
+                        .get(format!("{}/lol-perks/v1/currentpage", lc_info.base_url))
+                        .send()
+                        .await
+                    {
+                        if let Ok(page) = response.json::<RunePage>().await {
+                            if let Ok(page) = serde_json::to_string(&page) {
+                                let _ = events.send(Event::CapturedRunePage(page));
+                            }
+                        }
+                    }
+                }
+                let auto_accept = settings.auto_accept;
+                let match_log = settings.match_log;
+                let in_game_guard = settings.in_game_guard;
+                let spell_selection = settings.spell_selection;
+
+                for (feature, enabled) in [
+                    (Feature::AutoAccept, auto_accept),
+                    (Feature::PickBan, pick_ban_selection),
+                    (Feature::Spells, spell_selection),
+                    (Feature::Runes, rune_change),
+                ] {
+                    if !enabled {
+                        set_feature_state(
+                            &events,
+                            &mut feature_states,
+                            feature,
+                            FeatureState::Idle,
+                        );
+                    } else if feature_states[feature as usize] == FeatureState::Idle {
+                        set_feature_state(
+                            &events,
+                            &mut feature_states,
+                            feature,
+                            FeatureState::Armed,
+                        );
+                    }
                 }
-                Some("Lobby") => {
-                    *assigned_position.lock().unwrap() = None;
-                    *gameflow_status_clone.lock().unwrap() = "In Lobby".to_owned();
+
+                // Neither the gameflow session nor the live client data API report the game
+                // server ping, the round trip of this poll is the health indicator shown.
+                let poll_started = std::time::Instant::now();
+                let gameflow_response = match rest_client
+                    .get(format!("{}/lol-gameflow/v1/session", lc_info.base_url))
+                    .send()
+                    .await
+                {
+                    Ok(response) => {
+                        // only sent once it moved noticeably, every poll would flood the UI
+                        let latency = poll_started.elapsed();
+                        if last_latency.map_or(true, |last| {
+                            last.as_millis().abs_diff(latency.as_millis()) >= 20
+                        }) {
+                            let _ = events.send(Event::Latency(latency));
+                            last_latency = Some(latency);
+                        }
+                        failed_requests = 0;
+                        response
+                    }
+                    Err(_) => {
+                        // A quick client restart changes the port and auth without the connection
+                        // status ever flipping, so re-read the lockfile once requests keep failing.
+                        failed_requests += 1;
+                        if failed_requests >= MAX_FAILED_REQUESTS {
+                            reconnect(
+                                &mut lc_info,
+                                &mut rest_client,
+                                &mut summoner,
+                                &cert,
+                                &events,
+                            );
+                            failed_requests = 0;
+                        }
+                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                        return;
+                    }
+                };
+                let response_status = gameflow_response.status();
+                // The lockfile was readable but its password is stale, e.g. left over from a client
+                // that crashed. Keep re-reading it until the client writes a fresh one.
+                if response_status == reqwest::StatusCode::UNAUTHORIZED
+                    || response_status == reqwest::StatusCode::FORBIDDEN
+                {
+                    let _ = events.send(Event::Status(
+                        "Authentication failed — restart the client".to_owned(),
+                    ));
+                    reconnect(
+                        &mut lc_info,
+                        &mut rest_client,
+                        &mut summoner,
+                        &cert,
+                        &events,
+                    );
+                    tokio::time::sleep(tokio::time::Duration::from_secs(4)).await;
+                    return;
                 }
-                Some("ReadyCheck") => {
-                    if auto_accept {
-                        *gameflow_status_clone.lock().unwrap() = "Accepting match".to_owned();
-                        rest_client
-                            .post(format!(
-                                "https://127.0.0.1:{}/lol-matchmaking/v1/ready-check/accept",
-                                lc_info.port
+                let gameflow: serde_json::Value =
+                    gameflow_response.json().await.unwrap_or_default();
+                let phase = match gameflow_phase(response_status.as_u16(), &gameflow) {
+                    Ok(phase) => {
+                        if std::mem::take(&mut client_patching) {
+                            log_line("Client finished patching");
+                        }
+                        phase
+                    }
+                    Err(error) => {
+                        // Mid-patch most endpoints error out, the patcher tells it apart from a
+                        // broken client so the errors aren't logged on every poll.
+                        let patching = match rest_client
+                            .get(format!(
+                                "{}/patcher/v1/products/league_of_legends/state",
+                                lc_info.base_url
                             ))
                             .send()
                             .await
-                            .unwrap();
+                        {
+                            Ok(response) => response
+                                .json::<serde_json::Value>()
+                                .await
+                                .map_or(false, |state| is_patching(&state)),
+                            Err(_) => false,
+                        };
+                        if patching {
+                            if !client_patching {
+                                log_line("Client is patching");
+                                client_patching = true;
+                            }
+                            let _ = events.send(Event::Status("Client is patching…".to_owned()));
+                            tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+                            return;
+                        }
+                        log_line(format!("Client error: {}", error));
+                        let _ = events.send(Event::Status(format!("Client error: {}", error)));
+                        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                        return;
                     }
-                    *gameflow_status_clone.lock().unwrap() = "Match Found".to_owned();
+                };
+                let phase_changed = phase != last_phase.as_deref();
+                if phase_changed {
+                    log_line(format!("Gameflow phase {}", phase.unwrap_or("None")));
+                }
+                if sent_phase.as_deref() != Some(phase.unwrap_or("None")) {
+                    sent_phase = Some(phase.unwrap_or("None").to_owned());
+                    let _ = events.send(Event::Phase(sent_phase.clone()));
+                }
+
+                // A queue with its own automation set overrides the global toggles.
+                let queue_id = gameflow["gameData"]["queue"]["id"]
+                    .as_u64()
+                    .map(|queue_id| queue_id as u32);
+                let queue_automation = queue_override(&settings, queue_id, kill_switch);
+                let (auto_accept, pick_ban_selection, spell_selection) = match queue_automation {
+                    Some(queue) => (queue.auto_accept, queue.pick_ban, queue.spells),
+                    None => (auto_accept, pick_ban_selection, spell_selection),
+                };
+                last_phase = phase.map(|phase| phase.to_owned());
+                if phase_changed && phase != Some("ChampSelect") {
+                    spells_set = None;
+                    pick_now = false;
+                    ban_now = false;
                 }
-                Some("ChampSelect") => {
-                    let current_champ_select: serde_json::Value = rest_client
-                        .get(format!(
-                            "https://127.0.0.1:{}/lol-champ-select/v1/session",
-                            lc_info.port
+
+                for (id, accept) in std::mem::take(&mut invitation_answers) {
+                    let answered = rest_client
+                        .post(format!(
+                            "{}/lol-lobby/v2/received-invitations/{}/{}",
+                            lc_info.base_url,
+                            id,
+                            if accept { "accept" } else { "decline" }
                         ))
                         .send()
                         .await
-                        .unwrap()
-                        .json()
+                        .map_or(false, |response| response.status().is_success());
+                    let from = invitations
+                        .iter()
+                        .find(|invitation| invitation.invitationId == id)
+                        .map_or("unknown", |invitation| invitation.fromSummonerName.as_str());
+                    report_action(
+                        &events,
+                        match (answered, accept) {
+                            (true, true) => format!("Accepted invitation from {}", from),
+                            (true, false) => format!("Declined invitation from {}", from),
+                            (false, _) => format!("Couldn't answer invitation from {}", from),
+                        },
+                    );
+                }
+
+                // Invitations only matter while not in a queue or game.
+                let received = if matches!(phase, None | Some("Lobby")) {
+                    received_invitations(&rest_client, &lc_info.base_url)
                         .await
-                        .unwrap();
+                        .unwrap_or_default()
+                } else {
+                    Vec::new()
+                };
+                if received != invitations {
+                    invitations = received;
+                    let _ = events.send(Event::Invitations(invitations.clone()));
+                }
 
-                    let team_data_response: Vec<MyTeamData> =
-                        serde_json::from_value(current_champ_select["myTeam"].clone()).unwrap();
-                    let filtered_team_data: Vec<MyTeamData> = team_data_response
-                        .iter()
-                        .filter(|data| data.cellId == current_champ_select["localPlayerCellId"])
-                        .take(1)
-                        .cloned() // Limit to a maximum of 2 matches
-                        .collect();
-                    let extracted_team_data: (u32, u32, String) = filtered_team_data
-                        .iter()
-                        .map(|data| (data.spell1Id, data.spell2Id, data.assignedPosition.clone()))
-                        .next()
-                        .unwrap();
-
-                    *assigned_position.lock().unwrap() = Some(extracted_team_data.clone().2);
-                    if spell_selection {
-                        let spell1_clone = selected_image1_clone.lock().unwrap().clone();
-                        let spell2_clone = selected_image2_clone.lock().unwrap().clone();
-
-                        if spell1_clone.is_some() && spell2_clone.is_some() {
-                            if extracted_team_data.2.contains("jungle") {
-                                if spell1_clone.clone().unwrap() != "Smite".to_string()
-                                    && spell2_clone.clone().unwrap() != "Smite".to_string()
-                                {
-                                    if extracted_team_data.0 == 4
-                                    /*Flash*/
-                                    {
-                                        *spell1.lock().unwrap() = Some("Flash".to_owned());
-                                        *spell2.lock().unwrap() = Some("Smite".to_owned());
-                                        continue;
-                                    }
-                                    if extracted_team_data.0 == 6
-                                    /*Ghost*/
-                                    {
-                                        *spell1.lock().unwrap() = Some("Ghost".to_owned());
-                                        *spell2.lock().unwrap() = Some("Smite".to_owned());
-                                        continue;
+                if phase == Some("Lobby") {
+                    if let Ok(response) = rest_client
+                        .get(format!("{}/lol-lobby/v2/lobby", lc_info.base_url))
+                        .send()
+                        .await
+                    {
+                        if let Ok(lobby) = response.json::<serde_json::Value>().await {
+                            let local_member = &lobby["localMember"];
+                            let preferences: Vec<String> =
+                                ["firstPositionPreference", "secondPositionPreference"]
+                                    .iter()
+                                    .filter_map(|key| local_member[*key].as_str())
+                                    .map(str::to_owned)
+                                    .collect();
+                            if preferences != position_preferences {
+                                if let [first, second] = preferences.as_slice() {
+                                    let _ = events.send(Event::PositionPreferences(
+                                        first.clone(),
+                                        second.clone(),
+                                    ));
+                                }
+                            }
+                            position_preferences = preferences;
+                        }
+                    }
+                }
+
+                if settings.quiet_hours
+                    && in_quiet_hours(
+                        &settings.quiet_hours_start,
+                        &settings.quiet_hours_end,
+                        chrono::Local::now().time(),
+                    )
+                {
+                    let _ = events.send(Event::Status("Automation paused (schedule)".to_owned()));
+                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                    return;
+                }
+
+                // Safety net against logic bugs: only ready check and champion select may ever send
+                // actions, so re-confirm the phase with the client before entering those branches.
+                if in_game_guard
+                    && matches!(phase, Some("ReadyCheck") | Some("ChampSelect"))
+                    && !actions_allowed(&rest_client, &lc_info.base_url).await
+                {
+                    return;
+                }
+
+                match phase {
+                    Some("Matchmaking") => {
+                        assigned_position = None;
+                        let _ = events.send(Event::AssignedRole(assigned_position.clone()));
+                        let _ = events.send(Event::Status("Looking for a match".to_owned()));
+                        locked_champ = false;
+                        fallback_fired = false;
+                        last_pick_id = None;
+                        let _ = events.send(Event::PickMode(None));
+                        if phase_changed {
+                            for feature in Feature::ALL {
+                                if feature_states[feature as usize] != FeatureState::Idle {
+                                    set_feature_state(
+                                        &events,
+                                        &mut feature_states,
+                                        feature,
+                                        FeatureState::Armed,
+                                    );
+                                }
+                            }
+                        }
+                    }
+                    Some("Lobby") => {
+                        assigned_position = None;
+                        let _ = events.send(Event::AssignedRole(assigned_position.clone()));
+                        let _ = events.send(Event::Status("In Lobby".to_owned()));
+                    }
+                    Some("ReadyCheck") => {
+                        if auto_accept {
+                            let _ = events.send(Event::Status("Accepting match".to_owned()));
+                            let accepted = rest_client
+                                .post(format!(
+                                    "{}/lol-matchmaking/v1/ready-check/accept",
+                                    lc_info.base_url
+                                ))
+                                .send()
+                                .await
+                                .map_or(false, |response| response.status().is_success());
+                            set_feature_state(
+                                &events,
+                                &mut feature_states,
+                                Feature::AutoAccept,
+                                if accepted {
+                                    FeatureState::Fired
+                                } else {
+                                    FeatureState::Error
+                                },
+                            );
+                            if phase_changed && accepted {
+                                report_action(&events, "Accepted match");
+                            }
+                        }
+                        let _ = events.send(Event::Status("Match Found".to_owned()));
+                    }
+                    Some("ChampSelect") => {
+                        if phase_changed {
+                            let _ = events.send(Event::ClearActions);
+                            grid_fetched = None;
+                            outdated_reported = false;
+                            smite_checked = false;
+                            // action ids repeat between champion selects
+                            hovered_ban = None;
+                            ban_conflict_reported = false;
+                            autofill_reported = false;
+                            winrate_checked_for = None;
+                        }
+
+                        // checked once per champion select and stats URL, also when the option is
+                        // turned on midway
+                        if settings.winrate_picks
+                            && !settings.stats_url.is_empty()
+                            && winrate_checked_for.as_deref() != Some(settings.stats_url.as_str())
+                        {
+                            winrate_checked_for = Some(settings.stats_url.clone());
+                            let cached_patch = data_dragon_patch
+                                .as_ref()
+                                .filter(|(fetched, _)| {
+                                    fetched.elapsed() < DATA_DRAGON_PATCH_REFRESH
+                                })
+                                .map(|(_, patch)| patch.clone());
+                            let patch = match cached_patch {
+                                Some(patch) => patch,
+                                None => {
+                                    let patch = data_dragon_version(&reqwest::Client::new())
+                                        .await
+                                        .unwrap_or_default();
+                                    if !patch.is_empty() {
+                                        data_dragon_patch =
+                                            Some((std::time::Instant::now(), patch.clone()));
                                     }
-                                    if extracted_team_data.1 == 4 {
-                                        *spell1.lock().unwrap() = Some("Smite".to_owned());
-                                        *spell2.lock().unwrap() = Some("Flash".to_owned());
-                                        continue;
+                                    patch
+                                }
+                            };
+                            let cached =
+                                winrate_stats
+                                    .as_ref()
+                                    .map_or(false, |(cached_patch, url, _)| {
+                                        *cached_patch == patch && *url == settings.stats_url
+                                    });
+                            if !cached {
+                                match fetch_winrate_stats(&settings.stats_url).await {
+                                    Ok(stats) => {
+                                        winrate_stats =
+                                            Some((patch, settings.stats_url.clone(), stats))
                                     }
-                                    if extracted_team_data.1 == 6 {
-                                        *spell1.lock().unwrap() = Some("Smite".to_owned());
-                                        *spell2.lock().unwrap() = Some("Ghost".to_owned());
-                                        continue;
+                                    Err(_) => {
+                                        report_action(&events, "Couldn't load win rate stats")
                                     }
-                                    *spell1.lock().unwrap() = Some("Smite".to_owned());
-                                    continue;
                                 }
                             }
-                            let spell1_info = summoner_spells
-                                .iter()
-                                .find(|spell| spell.name == spell1_clone.clone().unwrap())
-                                .unwrap();
-                            let spell2_info = summoner_spells
-                                .iter()
-                                .find(|spell| spell.name == spell2_clone.clone().unwrap())
-                                .unwrap();
-
-                            let body = serde_json::json!({
-                                    "spell1Id": spell1_info.key,
-                                    "spell2Id": spell2_info.key
-                            });
+                        }
 
-                            rest_client
-                                .patch(format!(
-                                    "https://127.0.0.1:{}/lol-champ-select/v1/session/my-selection",
-                                    lc_info.port
+                        let Some(mut current_champ_select) =
+                            fetch_session(&rest_client, &lc_info.base_url).await
+                        else {
+                            return;
+                        };
+                        // Right as champion select opens the session can come back before myTeam
+                        // is populated, it's fetched again a couple of times before this poll is
+                        // given up.
+                        for _ in 0..2 {
+                            if my_team_member(&current_champ_select).is_some() {
+                                break;
+                            }
+                            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                            let Some(session) =
+                                fetch_session(&rest_client, &lc_info.base_url).await
+                            else {
+                                return;
+                            };
+                            current_champ_select = session;
+                        }
+                        let Some(my_team_data) = my_team_member(&current_champ_select) else {
+                            let _ = events.send(Event::Status(
+                                "Champion Selection, waiting for the team...".to_owned(),
+                            ));
+                            return;
+                        };
+                        // The timer counts down on every poll, the UI only needs the session when
+                        // anything else in it changed.
+                        let mut session_state = current_champ_select.clone();
+                        if let Some(session_state) = session_state.as_object_mut() {
+                            session_state.remove("timer");
+                        }
+                        if last_session.as_ref() != Some(&session_state) {
+                            let _ = events
+                                .send(Event::ChampSelectSession(current_champ_select.clone()));
+                            last_session = Some(session_state);
+                        }
+                        for id in enemy_champions(&current_champ_select).into_iter().flatten() {
+                            if champion_icons_sent.contains(&id) {
+                                continue;
+                            }
+                            let icon = match rest_client
+                                .get(format!(
+                                    "{}/lol-game-data/assets/v1/champion-icons/{}.png",
+                                    lc_info.base_url, id
                                 ))
-                                .json(&body)
                                 .send()
                                 .await
-                                .unwrap();
+                                .and_then(|response| response.error_for_status())
+                            {
+                                Ok(response) => response.bytes().await.ok(),
+                                Err(_) => None,
+                            };
+                            // an error page or a cut off download is fetched again next poll
+                            if let Some(bytes) = icon
+                                .filter(|bytes| egui_extras::image::load_image_bytes(bytes).is_ok())
+                            {
+                                let _ = events.send(Event::ChampionIcon(id, bytes.to_vec()));
+                                champion_icons_sent.insert(id);
+                            }
                         }
-                    }
 
-                    if !pick_ban_selection {
-                        *gameflow_status_clone.lock().unwrap() = "Champion Selection".to_owned();
-                        continue;
-                    }
+                        if trade_behavior != TradeBehavior::Off {
+                            let trades = current_champ_select["trades"]
+                                .as_array()
+                                .cloned()
+                                .unwrap_or_default();
+                            for trade in trades.iter().filter(|trade| trade["state"] == "RECEIVED")
+                            {
+                                // accepting a trade hands me the champion of the requesting cell
+                                let offered_champion = current_champ_select["myTeam"]
+                                    .as_array()
+                                    .and_then(|team| {
+                                        team.iter()
+                                            .find(|member| member["cellId"] == trade["cellId"])
+                                    })
+                                    .and_then(|member| member["championId"].as_u64());
+                                let accept = trade_behavior == TradeBehavior::AcceptAll
+                                    || champion_picks
+                                        .iter()
+                                        .any(|(id, _)| Some(*id as u64) == offered_champion);
+                                let answer = if accept { "accept" } else { "decline" };
 
-                    *gameflow_status_clone.lock().unwrap() =
-                        "Champion Selection with Auto-pick/ban ON".to_owned();
+                                let answered = rest_client
+                                    .post(format!(
+                                        "{}/lol-champ-select/v1/session/trades/{}/{}",
+                                        lc_info.base_url, trade["id"], answer
+                                    ))
+                                    .send()
+                                    .await
+                                    .map_or(false, |response| response.status().is_success());
 
-                    if champion_picks.len() == 0 && ban_picks.is_none() {
-                        continue;
-                    }
+                                let offered_name = champions_clone
+                                    .iter()
+                                    .find(|champion| Some(champion.id as u64) == offered_champion)
+                                    .map(|champion| champion.name.clone())
+                                    .unwrap_or_else(|| "unknown champion".to_owned());
+                                if answered {
+                                    report_action(
+                                        &events,
+                                        format!(
+                                            "{} trade for {}",
+                                            if accept { "Accepted" } else { "Declined" },
+                                            offered_name
+                                        ),
+                                    );
+                                } else {
+                                    report_action(
+                                        &events,
+                                        format!("Couldn't answer trade for {}", offered_name),
+                                    );
+                                }
+                            }
+                        }
 
-                    let current_champ_select: serde_json::Value = rest_client
-                        .get(format!(
-                            "https://127.0.0.1:{}/lol-champ-select/v1/session",
-                            lc_info.port
-                        ))
-                        .send()
-                        .await
-                        .unwrap()
-                        .json()
-                        .await
-                        .unwrap();
+                        if swap_behavior != SwapBehavior::Off {
+                            let swaps = current_champ_select["positionSwaps"]
+                                .as_array()
+                                .cloned()
+                                .unwrap_or_default();
+                            for swap in swaps.iter().filter(|swap| swap["state"] == "RECEIVED") {
+                                // accepting a swap hands me the position of the requesting cell,
+                                // the assigned position below is re-read so everything keyed on it
+                                // follows
+                                let offered_position = current_champ_select["myTeam"]
+                                    .as_array()
+                                    .and_then(|team| {
+                                        team.iter()
+                                            .find(|member| member["cellId"] == swap["cellId"])
+                                    })
+                                    .and_then(|member| member["assignedPosition"].as_str())
+                                    .filter(|position| !position.is_empty())
+                                    .unwrap_or("unknown position")
+                                    .to_owned();
+                                let accept = swap_behavior == SwapBehavior::AcceptAll;
+                                let answer = if accept { "accept" } else { "decline" };
 
-                    let action_response: Vec<Vec<ActionResponseData>> =
-                        serde_json::from_value(current_champ_select["actions"].clone()).unwrap();
-                    let filtered_action_data: Vec<ActionResponseData> = action_response
-                        .iter()
-                        .flatten()
-                        .filter(|data| {
-                            data.actorCellId == current_champ_select["localPlayerCellId"]
-                        })
-                        .take(2) // Limit to a maximum of 2 matches
-                        .cloned()
-                        .collect();
-                    let extracted_action_data: Vec<(i32, bool, String, bool)> =
-                        filtered_action_data
+                                let answered = rest_client
+                                    .post(format!(
+                                        "{}/lol-champ-select/v1/session/position-swaps/{}/{}",
+                                        lc_info.base_url, swap["id"], answer
+                                    ))
+                                    .send()
+                                    .await
+                                    .map_or(false, |response| response.status().is_success());
+
+                                if answered {
+                                    report_action(
+                                        &events,
+                                        format!(
+                                            "{} position swap to {}",
+                                            if accept { "Accepted" } else { "Declined" },
+                                            offered_position
+                                        ),
+                                    );
+                                }
+                            }
+                        }
+
+                        // Blind pick and ARAM leave the position empty, there's no role to show or
+                        // to adjust spells for so the selected pair is used as is.
+                        let position = normalize_position(&my_team_data.assignedPosition);
+                        assigned_position = position.clone();
+                        let _ = events.send(Event::AssignedRole(assigned_position.clone()));
+                        let spell_role_enabled = position.as_ref().map_or(true, |position| {
+                            !settings.spell_skip_roles.contains(position)
+                        });
+                        if spell_selection && spell_role_enabled {
+                            let mut spell1_clone = settings.spell1.clone();
+                            let mut spell2_clone = settings.spell2.clone();
+
+                            if spell1_clone.is_some() && spell2_clone.is_some() {
+                                // Only adjusted once per champion select so changing the spells by
+                                // hand afterwards isn't undone on the next poll.
+                                if position.as_deref() == Some("jungle") && !smite_checked {
+                                    smite_checked = true;
+                                    let (spell1, spell2) = jungle_spells(
+                                        spell1_clone.as_deref().unwrap(),
+                                        spell2_clone.as_deref().unwrap(),
+                                    );
+                                    if Some(&spell1) != spell1_clone.as_ref()
+                                        || Some(&spell2) != spell2_clone.as_ref()
+                                    {
+                                        spell1_clone = Some(spell1);
+                                        spell2_clone = Some(spell2);
+                                        settings.spell1 = spell1_clone.clone();
+                                        settings.spell2 = spell2_clone.clone();
+                                        let _ = events.send(Event::Spells(
+                                            settings.spell1.clone(),
+                                            settings.spell2.clone(),
+                                        ));
+                                    }
+                                }
+                                // Sent once per champion select, again only when the selection
+                                // changes so spells changed in the client aren't overwritten on
+                                // every poll.
+                                let spells =
+                                    (spell1_clone.clone().unwrap(), spell2_clone.clone().unwrap());
+                                if spells_set.as_ref() != Some(&spells) {
+                                    // a name missing from summoner_spells.json has no id to send
+                                    let spell_key = |name: &str| {
+                                        summoner_spells
+                                            .iter()
+                                            .find(|spell| spell.name == name)
+                                            .map(|spell| spell.key)
+                                    };
+                                    let spell_error =
+                                        match (spell_key(&spells.0), spell_key(&spells.1)) {
+                                            (Some(spell1_key), Some(spell2_key)) => {
+                                                let body = serde_json::json!({
+                                                        "spell1Id": spell1_key,
+                                                        "spell2Id": spell2_key
+                                                });
+
+                                                match rest_client
+                                                    .patch(format!(
+                                                    "{}/lol-champ-select/v1/session/my-selection",
+                                                    lc_info.base_url
+                                                ))
+                                                    .json(&body)
+                                                    .send()
+                                                    .await
+                                                {
+                                                    Ok(response)
+                                                        if response.status().is_success() =>
+                                                    {
+                                                        None
+                                                    }
+                                                    Ok(response) => {
+                                                        let status = response.status();
+                                                        let error: serde_json::Value = response
+                                                            .json()
+                                                            .await
+                                                            .unwrap_or_default();
+                                                        Some(
+                                                            error["message"]
+                                                                .as_str()
+                                                                .map(|message| message.to_owned())
+                                                                .unwrap_or_else(|| {
+                                                                    status.to_string()
+                                                                }),
+                                                        )
+                                                    }
+                                                    Err(error) => Some(error.to_string()),
+                                                }
+                                            }
+                                            (None, _) => {
+                                                Some(format!("Unknown spell: {}", spells.0))
+                                            }
+                                            (_, None) => {
+                                                Some(format!("Unknown spell: {}", spells.1))
+                                            }
+                                        };
+                                    // only report a failure once instead of on every poll
+                                    if spell_error.is_some() && spell_error != last_spell_error {
+                                        report_action(
+                                            &events,
+                                            format!(
+                                                "Couldn't set spells: {}",
+                                                spell_error.clone().unwrap()
+                                            ),
+                                        );
+                                    }
+                                    set_feature_state(
+                                        &events,
+                                        &mut feature_states,
+                                        Feature::Spells,
+                                        if spell_error.is_some() {
+                                            FeatureState::Error
+                                        } else {
+                                            FeatureState::Fired
+                                        },
+                                    );
+                                    if spell_error.is_none() {
+                                        report_action(
+                                            &events,
+                                            format!("Set spells {} and {}", spells.0, spells.1),
+                                        );
+                                        spells_set = Some(spells);
+                                    }
+                                    last_spell_error = spell_error;
+                                }
+                            }
+                        }
+
+                        // Pick Now and Ban Now act once, even with the automation off.
+                        let force_pick = std::mem::take(&mut pick_now);
+                        let force_ban = std::mem::take(&mut ban_now);
+
+                        if !pick_ban_selection && !force_pick && !force_ban {
+                            let _ = events.send(Event::Status("Champion Selection".to_owned()));
+                            return;
+                        }
+
+                        // while armed the turn status below is the only champion select status
+                        if champion_picks.len() == 0
+                            && ban_picks.is_empty()
+                            && counter_bans.is_empty()
+                        {
+                            let _ = events.send(Event::Status("Champion Selection".to_owned()));
+                            return;
+                        }
+
+                        // The actions can be half written as planning ends, the session is fetched
+                        // again shortly after and this poll is skipped if they still don't parse.
+                        let mut action_response =
+                            serde_json::from_value::<Vec<Vec<ActionResponseData>>>(
+                                current_champ_select["actions"].clone(),
+                            );
+                        for _ in 0..2 {
+                            if action_response.is_ok() {
+                                break;
+                            }
+                            tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+                            let Some(session) =
+                                fetch_session(&rest_client, &lc_info.base_url).await
+                            else {
+                                return;
+                            };
+                            // my cell was only checked on the session fetched above
+                            if my_team_member(&session).is_none() {
+                                return;
+                            }
+                            current_champ_select = session;
+                            action_response =
+                                serde_json::from_value(current_champ_select["actions"].clone());
+                        }
+                        let action_response = match action_response {
+                            Ok(action_response) => action_response,
+                            Err(err) => {
+                                log_line(format!(
+                                    "Couldn't parse champion select actions: {}",
+                                    err
+                                ));
+                                return;
+                            }
+                        };
+
+                        // Nothing is sent without a timer, the session is between phases.
+                        let timer = Timer::from_session(&current_champ_select);
+                        let actions_open = timer.as_ref().map_or(false, Timer::allows_actions);
+
+                        let my_actions: Vec<&ActionResponseData> = action_response
+                            .iter()
+                            .flatten()
+                            .filter(|data| {
+                                data.actorCellId == current_champ_select["localPlayerCellId"]
+                            })
+                            .collect();
+
+                        // Modes with more than one ban give me several ban actions, they're done in
+                        // order. Without a ban action (e.g. blind pick) the ban counts as done.
+                        let ban_actions: Vec<&&ActionResponseData> = my_actions
+                            .iter()
+                            .filter(|data| data.r#type == "ban")
+                            .collect();
+                        let ban_index = ban_actions
+                            .iter()
+                            .position(|data| !data.completed)
+                            .unwrap_or(0);
+                        let (ban_id, ban_is_in_progress, ban_completed) = ban_actions
+                            .get(ban_index)
+                            .map(|data| (data.id, data.isInProgress, data.completed))
+                            .unwrap_or((0, false, true));
+
+                        // Some modes give me more than one pick turn, each slot then fills its own
+                        // turn instead of acting as a priority list for a single pick.
+                        let pick_actions: Vec<&&ActionResponseData> = my_actions
                             .iter()
-                            .map(|data| {
-                                (
-                                    data.id,
-                                    data.isInProgress,
-                                    data.r#type.clone(),
-                                    data.completed,
-                                )
-                            })
+                            .filter(|data| data.r#type == "pick")
                             .collect();
+                        let pick_index = pick_actions
+                            .iter()
+                            .position(|data| !data.completed)
+                            .unwrap_or(0);
+                        let (pick_id, pick_is_in_progress, pick_completed) = pick_actions
+                            .get(pick_index)
+                            .map(|data| (data.id, data.isInProgress, data.completed))
+                            .unwrap_or((0, false, false));
+                        let pick_mode = if pick_actions.len() > 1 {
+                            PickMode::Separate
+                        } else {
+                            PickMode::Priority
+                        };
+                        let _ = events.send(Event::PickMode(Some(pick_mode)));
 
-                    let (ban_id, ban_is_in_progress, _type1, ban_completed) = extracted_action_data
-                        .get(0)
-                        .cloned()
-                        .unwrap_or((0, false, "".to_string(), false));
-                    let (pick_id, pick_is_in_progress, _type2, pick_completed) =
-                        extracted_action_data.get(1).cloned().unwrap_or((
-                            0,
-                            false,
-                            "".to_string(),
-                            false,
-                        ));
+                        // Also runs on the first poll after a restart mid champion select, where my
+                        // pick may already be locked in.
+                        if last_pick_id != Some(pick_id) {
+                            locked_champ = pick_completed;
+                            fallback_fired = false;
+                            last_pick_id = Some(pick_id);
+                        }
 
-                    if ban_picks.is_some() {
-                        if !ban_picks.as_ref().unwrap().1.is_empty() {
-                            let ban_body = serde_json::json!({
-                                    "actorCellId": current_champ_select["localPlayerCellId"],
-                                    "championId": &ban_picks.as_ref().unwrap().0,
-                                    "completed": true,
-                                    "id": &ban_id,
-                                    "isAllyAction": true,
-                                    "type": "ban"
-                            });
-                            let ban_champ_info: serde_json::Value = rest_client
+                        let mut candidates =
+                            pick_candidates(&champion_picks, pick_mode, pick_index);
+                        if let Some((_, _, stats)) = winrate_stats
+                            .as_ref()
+                            .filter(|_| settings.winrate_picks && pick_mode == PickMode::Priority)
+                        {
+                            order_by_winrate(&mut candidates, stats, assigned_position.as_deref());
+                        }
+
+                        // One list for the whole champion select instead of a request per candidate
+                        // on every poll, refreshed every few seconds.
+                        if grid_fetched
+                            .map_or(true, |fetched| fetched.elapsed() >= GRID_CHAMPIONS_REFRESH)
+                        {
+                            if let Ok(response) = rest_client
                                 .get(format!(
-                                    "https://127.0.0.1:{}/lol-champ-select/v1/grid-champions/{}",
-                                    lc_info.port,
-                                    &ban_picks.as_ref().unwrap().0
+                                    "{}/lol-champ-select/v1/all-grid-champions",
+                                    lc_info.base_url
                                 ))
                                 .send()
                                 .await
-                                .unwrap()
-                                .json()
-                                .await
-                                .unwrap();
-
-                            if ban_is_in_progress
-                                && !ban_completed
-                                && ban_champ_info["selectionStatus"]["pickedByOtherOrBanned"]
-                                    != true
-                                && current_champ_select["timer"]["phase"] != "PLANNING"
                             {
-                                rest_client
-                                    .patch(format!(
-                                    "https://127.0.0.1:{}/lol-champ-select/v1/session/actions/{}",
-                                    lc_info.port, ban_id
-                                ))
-                                    .json(&ban_body)
-                                    .send()
-                                    .await
-                                    .unwrap();
-                                tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+                                if let Ok(champions) = response.json().await {
+                                    grid_champions = champions;
+                                    grid_fetched = Some(std::time::Instant::now());
+                                }
                             }
                         }
-                    }
+                        // The grid lists every champion the client knows, configured ids missing
+                        // from it come from a stale champions.json and are never sent.
+                        let unknown_champion = !grid_champions.is_empty()
+                            && candidates
+                                .iter()
+                                .chain(ban_picks.iter())
+                                .filter(|(id, name)| *id != 0 && !name.is_empty())
+                                .any(|(id, _)| {
+                                    !grid_champions.iter().any(|champion| champion["id"] == *id)
+                                });
+                        if unknown_champion && !outdated_reported {
+                            outdated_reported = true;
+                            report_action(&events, "Champion data out of date, please update");
+                            let _ = events.send(Event::ChampionDataOutdated);
+                        }
 
-                    if champion_picks.len() != 0 {
-                        if champion_picks.get(0).unwrap().1.is_empty()
-                            && champion_picks.get(1).unwrap().1.is_empty()
-                        {
-                            continue;
+                        let is_available = |id: u32| {
+                            grid_champions.iter().any(|champion| {
+                                champion["id"] == id
+                                    && champion["selectionStatus"]["pickedByOtherOrBanned"] != true
+                            })
+                        };
+
+                        // Without the filter the client decides, which fails the lock for a
+                        // champion that's neither owned nor in the free rotation.
+                        let is_pickable = |id: u32| {
+                            is_available(id)
+                                && (!settings.playable_picks
+                                    || grid_champions.iter().any(|champion| {
+                                        champion["id"] == id && is_playable(champion)
+                                    }))
+                        };
+
+                        let is_my_pick = |id: u32| {
+                            settings.protect_picks
+                                && champion_picks.iter().any(|(pick_id, _)| *pick_id == id)
+                        };
+
+                        // Each ban turn takes the next entry of my ban list, one that's been banned
+                        // or picked since is passed over for the one after it.
+                        let ban_picks = ban_picks
+                            .iter()
+                            .skip(ban_index)
+                            .find(|(id, _)| is_available(*id))
+                            .or(ban_picks.get(ban_index))
+                            .cloned();
+                        // A rule for my position or queue replaces my usual first ban.
+                        let ban_picks = if settings.skip_ban || ban_index > 0 {
+                            ban_picks
+                        } else {
+                            rule_ban(&settings.ban_rules, assigned_position.as_deref(), queue_id)
+                                .or(ban_picks)
+                        };
+                        // A watched champion hovered by an enemy is banned instead of my usual ban.
+                        let ban_picks = counter_ban(&current_champ_select, &counter_bans, |id| {
+                            is_available(id) && !is_my_pick(id)
+                        })
+                        .or(ban_picks);
+                        let ban_picks = match ban_picks {
+                            Some((id, name)) if is_my_pick(id) => {
+                                if !ban_conflict_reported {
+                                    ban_conflict_reported = true;
+                                    report_action(
+                                        &events,
+                                        format!("Not banning {}, it's one of your picks", name),
+                                    );
+                                }
+                                None
+                            }
+                            ban_picks => ban_picks,
+                        };
+
+                        if pick_ban_selection {
+                            let locked = pick_actions
+                                .get(pick_index)
+                                .filter(|data| data.completed)
+                                .and_then(|data| {
+                                    champions_clone
+                                        .iter()
+                                        .find(|champion| champion.id == data.championId)
+                                })
+                                .map(|champion| champion.name.as_str());
+                            let _ = events.send(Event::Status(turn_status(
+                                ban_picks.is_some() && !ban_completed,
+                                ban_is_in_progress && actions_open,
+                                pick_is_in_progress && actions_open,
+                                locked,
+                            )));
                         }
-                        if !champion_picks.get(0).unwrap().1.is_empty() {
-                            let pick_champ_info: serde_json::Value = rest_client
-                                .get(format!(
-                                    "https://127.0.0.1:{}/lol-champ-select/v1/grid-champions/{}",
-                                    lc_info.port,
-                                    champion_picks.get(0).unwrap().0
-                                ))
-                                .send()
-                                .await
-                                .unwrap()
-                                .json()
-                                .await
-                                .unwrap();
 
-                            let pick_body = serde_json::json!({
-                                    "actorCellId": current_champ_select["localPlayerCellId"],
-                                    "championId": champion_picks.get(0).unwrap().0,
-                                    "completed": true,
-                                    "id": &pick_id,
-                                    "isAllyAction": true,
-                                    "type": "pick"
-                            });
+                        if force_ban && !(ban_is_in_progress && !ban_completed && actions_open) {
+                            report_action(&events, "Ban Now: it's not your ban turn");
+                        }
 
-                            if !pick_is_in_progress
-                                && pick_completed
-                                && !ban_is_in_progress
-                                && ban_completed
-                                || current_champ_select["timer"]["phase"] == "PLANNING"
+                        if ban_picks.is_some() && (pick_ban_selection || force_ban) {
+                            // id 0 is never a real champion, only banning nothing uses it
+                            if !ban_picks.as_ref().unwrap().1.is_empty()
+                                && ban_picks.as_ref().unwrap().0 != 0
                             {
-                                continue;
+                                // A hovered ban shows my team what I'm about to ban and is only
+                                // committed when the ban turn is about to run out. A decoy is
+                                // hovered in its place until then.
+                                let decoy = decoy_ban.clone().filter(|(id, _)| {
+                                    *id != ban_picks.as_ref().unwrap().0 && is_available(*id)
+                                });
+                                let commit_ban = force_ban
+                                    || !(hover_ban || decoy.is_some())
+                                    || timer.as_ref().map_or(true, |timer| {
+                                        timer.adjustedTimeLeftInPhase <= BAN_COMMIT_WINDOW_MS
+                                    });
+                                let hovered = decoy
+                                    .as_ref()
+                                    .filter(|_| !commit_ban)
+                                    .unwrap_or(ban_picks.as_ref().unwrap());
+                                let ban_body = serde_json::json!({
+                                        "actorCellId": current_champ_select["localPlayerCellId"],
+                                        "championId": hovered.0,
+                                        "completed": commit_ban,
+                                        "id": &ban_id,
+                                        "isAllyAction": true,
+                                        "type": "ban"
+                                });
+                                if ban_is_in_progress
+                                    && !ban_completed
+                                    && is_available(ban_picks.as_ref().unwrap().0)
+                                    && actions_open
+                                    && (commit_ban || hovered_ban != Some((ban_id, hovered.0)))
+                                {
+                                    let sent = patch_action(
+                                        &rest_client,
+                                        &lc_info.base_url,
+                                        ban_id,
+                                        &ban_body,
+                                        action_retries,
+                                        &events,
+                                    )
+                                    .await;
+                                    if !sent {
+                                        report_action(
+                                            &events,
+                                            format!(
+                                                "Couldn't ban {}",
+                                                ban_picks.as_ref().unwrap().1
+                                            ),
+                                        );
+                                        set_feature_state(
+                                            &events,
+                                            &mut feature_states,
+                                            Feature::PickBan,
+                                            FeatureState::Error,
+                                        );
+                                    } else if commit_ban {
+                                        report_action(
+                                            &events,
+                                            format!("Banned {}", ban_picks.as_ref().unwrap().1),
+                                        );
+                                        set_feature_state(
+                                            &events,
+                                            &mut feature_states,
+                                            Feature::PickBan,
+                                            FeatureState::Fired,
+                                        );
+                                        tokio::time::sleep(tokio::time::Duration::from_secs(10))
+                                            .await;
+                                    } else {
+                                        hovered_ban = Some((ban_id, hovered.0));
+                                        report_action(
+                                            &events,
+                                            if decoy.is_some() {
+                                                format!("Hovering decoy {}", hovered.1)
+                                            } else {
+                                                format!("Hovering ban {}", hovered.1)
+                                            },
+                                        );
+                                    }
+                                }
                             }
+                        }
 
-                            if !pick_is_in_progress {
-                                continue;
-                            }
-                            if pick_champ_info["selectionStatus"]["pickedByOtherOrBanned"] != true {
-                                if pick_is_in_progress
-                                    && !pick_completed
-                                    && !ban_is_in_progress
-                                    && ban_completed
-                                    && pick_champ_info["selectionStatus"]["pickedByOtherOrBanned"]
-                                        != true
-                                    && !locked_champ
-                                {
-                                    if rune_change {
-                                        // TODO:
+                        // Pick Now doesn't wait for my ban to be done first.
+                        // Without known lobby positions (e.g. started mid champion select) or a
+                        // position the pick isn't held back.
+                        let pick_allowed = !settings.autofill_picks_only
+                            || position_preferences.is_empty()
+                            || position.as_deref().map_or(true, |position| {
+                                is_autofilled(position, &position_preferences)
+                            });
+                        if pick_ban_selection && !pick_allowed && !autofill_reported {
+                            autofill_reported = true;
+                            report_action(
+                                &events,
+                                "In one of your lobby positions, pick left to you",
+                            );
+                        }
+                        let my_pick_turn = ((pick_ban_selection && pick_allowed) || force_pick)
+                            && pick_is_in_progress
+                            && !pick_completed
+                            && (force_pick || (!ban_is_in_progress && ban_completed))
+                            && actions_open;
+                        if force_pick && !my_pick_turn {
+                            report_action(&events, "Pick Now: it's not your pick turn");
+                        } else if force_pick && locked_champ {
+                            report_action(&events, "Pick Now: already locked in");
+                        }
+                        if pick_fallback != PickFallback::Nothing
+                            && my_pick_turn
+                            && !locked_champ
+                            && !fallback_fired
+                            && candidates.iter().any(|(_, name)| !name.is_empty())
+                        {
+                            if !candidates
+                                .iter()
+                                .any(|(id, name)| !name.is_empty() && is_pickable(*id))
+                            {
+                                let fallback_pick = match pick_fallback {
+                                    PickFallback::RandomOwned => {
+                                        let owned: Vec<u32> = grid_champions
+                                            .iter()
+                                            .filter(|champion| {
+                                                champion["owned"] == true
+                                                    && champion["selectionStatus"]
+                                                        ["pickedByOtherOrBanned"]
+                                                        != true
+                                            })
+                                            .filter_map(|champion| champion["id"].as_u64())
+                                            .map(|id| id as u32)
+                                            .collect();
+                                        let seed = std::time::SystemTime::now()
+                                            .duration_since(std::time::UNIX_EPOCH)
+                                            .unwrap_or_default()
+                                            .subsec_nanos()
+                                            as usize;
+                                        owned.get(seed % owned.len().max(1)).copied()
                                     }
-                                    rest_client
-                                        .patch(format!(
-                                    "https://127.0.0.1:{}/lol-champ-select/v1/session/actions/{}",
-                                    lc_info.port, pick_id
-                                ))
-                                        .json(&pick_body)
-                                        .send()
-                                        .await
-                                        .unwrap();
-                                    locked_champ = true;
-                                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                                    PickFallback::SecondaryPool => secondary_picks
+                                        .iter()
+                                        .map(|(id, _)| *id)
+                                        .find(|id| is_pickable(*id)),
+                                    PickFallback::Nothing => None,
+                                };
+
+                                if let Some(fallback_id) = fallback_pick {
+                                    let lock_in = pick_fallback == PickFallback::RandomOwned;
+                                    let fallback_body = serde_json::json!({
+                                            "actorCellId": current_champ_select["localPlayerCellId"],
+                                            "championId": fallback_id,
+                                            "completed": lock_in,
+                                            "id": &pick_id,
+                                            "isAllyAction": true,
+                                            "type": "pick"
+                                    });
+                                    let sent = patch_action(
+                                        &rest_client,
+                                        &lc_info.base_url,
+                                        pick_id,
+                                        &fallback_body,
+                                        action_retries,
+                                        &events,
+                                    )
+                                    .await;
+
+                                    let fallback_name = champions_clone
+                                        .iter()
+                                        .find(|champion| champion.id == fallback_id)
+                                        .map(|champion| champion.name.clone())
+                                        .unwrap_or_else(|| fallback_id.to_string());
+                                    if !sent {
+                                        report_action(
+                                            &events,
+                                            format!("Couldn't pick {}", fallback_name),
+                                        );
+                                        set_feature_state(
+                                            &events,
+                                            &mut feature_states,
+                                            Feature::PickBan,
+                                            FeatureState::Error,
+                                        );
+                                        return;
+                                    }
+                                    set_feature_state(
+                                        &events,
+                                        &mut feature_states,
+                                        Feature::PickBan,
+                                        FeatureState::Fired,
+                                    );
+                                    if lock_in {
+                                        locked_champ = true;
+                                        report_action(
+                                            &events,
+                                            format!(
+                                                "Picks unavailable, locked random {}",
+                                                fallback_name
+                                            ),
+                                        );
+                                    } else {
+                                        report_action(
+                                            &events,
+                                            format!(
+                                                "Picks unavailable, hovered {} from secondary pool",
+                                                fallback_name
+                                            ),
+                                        );
+                                    }
+                                } else {
+                                    report_action(
+                                        &events,
+                                        format!(
+                                            "Picks unavailable, no {} champion available",
+                                            if pick_fallback == PickFallback::RandomOwned {
+                                                "owned"
+                                            } else {
+                                                "secondary pool"
+                                            }
+                                        ),
+                                    );
                                 }
+                                fallback_fired = true;
+                                return;
                             }
                         }
 
-                        if champion_picks.len() == 1 {
-                            continue;
+                        if !my_pick_turn || locked_champ {
+                            return;
                         }
 
-                        if !champion_picks.get(1).unwrap().1.is_empty() {
-                            let pick_champ_info: serde_json::Value = rest_client
-                                .get(format!(
-                                    "https://127.0.0.1:{}/lol-champ-select/v1/grid-champions/{}",
-                                    lc_info.port,
-                                    champion_picks.get(1).unwrap().0
-                                ))
-                                .send()
-                                .await
-                                .unwrap()
-                                .json()
-                                .await
-                                .unwrap();
+                        for (champion_id, champion_name) in
+                            candidates.iter().filter(|(_, name)| !name.is_empty())
+                        {
+                            if !is_pickable(*champion_id) {
+                                continue;
+                            }
 
                             let pick_body = serde_json::json!({
                                     "actorCellId": current_champ_select["localPlayerCellId"],
-                                    "championId": champion_picks.get(1).unwrap().0,
+                                    "championId": champion_id,
                                     "completed": true,
                                     "id": &pick_id,
                                     "isAllyAction": true,
                                     "type": "pick"
                             });
 
-                            if !pick_is_in_progress
-                                && pick_completed
-                                && !ban_is_in_progress
-                                && ban_completed
-                                || current_champ_select["timer"]["phase"] == "PLANNING"
-                            {
-                                continue;
+                            if rune_change {
+                                if let Some(page) = rune_pages.get(champion_id) {
+                                    let state = match apply_rune_page(
+                                        &rest_client,
+                                        &lc_info.base_url,
+                                        page,
+                                    )
+                                    .await
+                                    {
+                                        Ok(()) => {
+                                            report_action(
+                                                &events,
+                                                format!("Applied rune page \"{}\"", page.name),
+                                            );
+                                            FeatureState::Fired
+                                        }
+                                        Err(_) => {
+                                            report_action(
+                                                &events,
+                                                format!(
+                                                    "Couldn't apply rune page \"{}\"",
+                                                    page.name
+                                                ),
+                                            );
+                                            FeatureState::Error
+                                        }
+                                    };
+                                    set_feature_state(
+                                        &events,
+                                        &mut feature_states,
+                                        Feature::Runes,
+                                        state,
+                                    );
+                                }
                             }
-
-                            if !pick_is_in_progress {
-                                continue;
+                            let sent = patch_action(
+                                &rest_client,
+                                &lc_info.base_url,
+                                pick_id,
+                                &pick_body,
+                                action_retries,
+                                &events,
+                            )
+                            .await;
+                            if !sent {
+                                report_action(&events, format!("Couldn't lock {}", champion_name));
+                                set_feature_state(
+                                    &events,
+                                    &mut feature_states,
+                                    Feature::PickBan,
+                                    FeatureState::Error,
+                                );
+                                break;
                             }
-                            if pick_champ_info["selectionStatus"]["pickedByOtherOrBanned"] != true {
-                                if pick_is_in_progress
-                                    && !pick_completed
-                                    && !ban_is_in_progress
-                                    && ban_completed
-                                    && pick_champ_info["selectionStatus"]["pickedByOtherOrBanned"]
-                                        != true
-                                    && !locked_champ
-                                {
-                                    if rune_change {
-                                        // TODO:
-                                    }
-                                    rest_client
-                                        .patch(format!(
-                                    "https://127.0.0.1:{}/lol-champ-select/v1/session/actions/{}",
-                                    lc_info.port, pick_id
+                            // The client can accept the request without taking the pick, so the
+                            // lock only counts once my pick action has completed with the champion.
+                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                            let confirmed = fetch_session(&rest_client, &lc_info.base_url)
+                                .await
+                                .map_or(false, |session| pick_locked(&session, *champion_id));
+                            if !confirmed {
+                                report_action(
+                                    &events,
+                                    format!("Lock of {} didn't go through", champion_name),
+                                );
+                                set_feature_state(
+                                    &events,
+                                    &mut feature_states,
+                                    Feature::PickBan,
+                                    FeatureState::Error,
+                                );
+                                break;
+                            }
+                            report_action(&events, format!("Locked {}", champion_name));
+                            set_feature_state(
+                                &events,
+                                &mut feature_states,
+                                Feature::PickBan,
+                                FeatureState::Fired,
+                            );
+                            locked_champ = true;
+                            break;
+                        }
+                    }
+                    Some("InProgress") => {
+                        if !settings.live_game_stats {
+                            let _ = events.send(Event::Status("Game in progress...".to_owned()));
+                            tokio::time::sleep(tokio::time::Duration::from_secs(20)).await;
+                            return;
+                        }
+                        // the live client API is only up once the game has loaded
+                        let response = match &live_client {
+                            Some(live_client) => live_client.get(LIVE_CLIENT_URL).send().await.ok(),
+                            None => None,
+                        };
+                        let live_game = match response {
+                            Some(response) => response.json::<serde_json::Value>().await.ok(),
+                            None => None,
+                        };
+                        let status = live_game
+                            .as_ref()
+                            .and_then(live_game_summary)
+                            .unwrap_or_else(|| "Game in progress...".to_owned());
+                        let _ = events.send(Event::Status(status));
+                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    }
+                    Some("WaitingForStats") => {
+                        let _ = events.send(Event::Status("Waiting for Stats".to_owned()));
+                        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                    }
+                    Some("PreEndOfGame") => {
+                        let _ = events.send(Event::Status("Game in progress...".to_owned()));
+                        tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+                    }
+                    Some("EndOfGame") => {
+                        if match_log && phase_changed {
+                            let role = assigned_position.clone().unwrap_or_default();
+                            if let Ok(response) = rest_client
+                                .get(format!(
+                                    "{}/lol-end-of-game/v1/eog-stats-block",
+                                    lc_info.base_url
                                 ))
-                                        .json(&pick_body)
-                                        .send()
-                                        .await
-                                        .unwrap();
-                                    locked_champ = true;
-                                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                                .send()
+                                .await
+                            {
+                                if let Ok(eog_stats) = response.json::<serde_json::Value>().await {
+                                    if append_match_summary(&eog_stats, &champions_clone, &role)
+                                        .is_err()
+                                    {
+                                        report_action(&events, "Couldn't write match log");
+                                    }
                                 }
                             }
                         }
+                        assigned_position = None;
+                        let _ = events.send(Event::AssignedRole(assigned_position.clone()));
+                        let _ = events.send(Event::Status("Game Ending...".to_owned()));
+                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    }
+                    Some(unimplemented_phase) => {
+                        assigned_position = None;
+                        let _ = events.send(Event::AssignedRole(assigned_position.clone()));
+                        let _ = events.send(Event::Status(
+                            format!("Unimplemented Phase: {}", unimplemented_phase).to_owned(),
+                        ));
+                        tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+                    }
+                    None => {
+                        let _ = events.send(Event::Status("Idling...".to_owned()));
                     }
                 }
-                Some("InProgress") => {
-                    *gameflow_status_clone.lock().unwrap() = "Game in progress...".to_owned();
-                    tokio::time::sleep(tokio::time::Duration::from_secs(20)).await;
-                }
-                Some("WaitingForStats") => {
-                    *gameflow_status_clone.lock().unwrap() = "Waiting for Stats".to_owned();
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                }
-                Some("PreEndOfGame") => {
-                    *gameflow_status_clone.lock().unwrap() = "Game in progress...".to_owned();
-                    tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-                }
-                Some("EndOfGame") => {
-                    *assigned_position.lock().unwrap() = None;
-                    *gameflow_status_clone.lock().unwrap() = "Game Ending...".to_owned();
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                }
-                Some(unimplemented_phase) => {
-                    *assigned_position.lock().unwrap() = None;
-                    *gameflow_status_clone.lock().unwrap() =
-                        format!("Unimplemented Phase: {}", unimplemented_phase).to_owned();
-                    tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-                }
-                None => {
-                    *gameflow_status_clone.lock().unwrap() = "Idling...".to_owned();
-                }
+            };
+            if AssertUnwindSafe(poll).catch_unwind().await.is_err() {
+                report_action(&events, "Automation error, skipped this poll");
+                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
             }
         }
     });
 
-    eframe::run_native("Circuit Watcher", options, Box::new(|_cc| Box::new(app)))?;
+    let kill_switch_code = Arc::clone(&app.kill_switch_code);
+    let automation_killed = Arc::clone(&app.automation_killed);
+    let kill_switch_commands = app.commands.clone();
+    let automation = [
+        Arc::clone(&app.auto_accept),
+        Arc::clone(&app.pick_ban_selection),
+        Arc::clone(&app.spell_selection),
+        Arc::clone(&app.rune_page_selection),
+    ];
+
+    let runtime = tokio::runtime::Handle::current();
+    eframe::run_native(
+        "Circuit Watcher",
+        options,
+        Box::new(move |cc| {
+            let relay_ctx = cc.egui_ctx.clone();
+            runtime.spawn(async move {
+                while let Some(event) = worker_events.recv().await {
+                    if ui_events.send(event).is_err() {
+                        break;
+                    }
+                    relay_ctx.request_repaint();
+                }
+            });
+            let ctx = cc.egui_ctx.clone();
+            std::thread::spawn(move || {
+                run_kill_switch(kill_switch_code, || {
+                    for enabled in &automation {
+                        enabled.store(false, Ordering::SeqCst);
+                    }
+                    automation_killed.store(true, Ordering::SeqCst);
+                    let _ = kill_switch_commands.send(Command::KillSwitch);
+                    ctx.request_repaint();
+                })
+            });
+            Box::new(app)
+        }),
+    )?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_scenarios_answer_the_gameflow_session() {
+        for (name, fixture) in MOCK_SCENARIOS {
+            let routes: HashMap<String, serde_json::Value> = serde_json::from_str(fixture).unwrap();
+            let (status, _) = mock_response(&routes, "GET", "/lol-gameflow/v1/session");
+            assert_eq!(status, "200 OK", "{}", name);
+        }
+    }
+
+    #[test]
+    fn mock_routes_fall_back_to_wildcards() {
+        let routes: HashMap<String, serde_json::Value> =
+            serde_json::from_str(MOCK_SCENARIOS[1].1).unwrap();
+        let (status, body) =
+            mock_response(&routes, "GET", "/lol-champ-select/v1/grid-champions/103");
+        assert_eq!(status, "200 OK");
+        assert!(body.contains("pickedByOtherOrBanned"));
+        assert_eq!(
+            mock_response(&routes, "GET", "/lol-unknown").0,
+            "404 Not Found"
+        );
+        assert_eq!(
+            mock_response(&routes, "PATCH", "/lol-unknown").0,
+            "204 No Content"
+        );
+    }
+
+    #[test]
+    fn plan_grid_renders_picks_bans_and_spells() {
+        let mut images = ImageCache::new(IMAGE_CACHE_CAPACITY);
+        images.insert_raw(
+            "Flash".to_owned(),
+            include_bytes!("../utils/images/flash.png").to_vec(),
+        );
+        let picks = vec![(103, "Ahri".to_owned()), (0, String::new())];
+        let bans = vec![(157, "Yasuo".to_owned())];
+        let spells = [Some("Flash".to_owned()), None];
+
+        let ctx = egui::Context::default();
+        let mut rect = None;
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                rect = Some(plan_grid(ui, &mut images, &picks, &bans, &spells));
+            });
+        });
+        let rect = rect.expect("the plan should be laid out");
+        assert!(rect.width() > 0.0 && rect.height() > 0.0);
+    }
+
+    #[test]
+    fn kill_switch_keys_are_letters_or_digits() {
+        assert_eq!(hotkey_code("k"), Some(0x4B));
+        assert_eq!(hotkey_code("7"), Some(0x37));
+        assert_eq!(hotkey_code(""), None);
+        assert_eq!(hotkey_code("+"), None);
+        assert_eq!(hotkey_code("KK"), None);
+    }
+
+    #[test]
+    fn repaints_quickly_only_around_champ_select() {
+        assert_eq!(
+            repaint_interval(Some("ChampSelect"), true),
+            std::time::Duration::from_millis(250)
+        );
+        assert_eq!(
+            repaint_interval(Some("ReadyCheck"), false),
+            std::time::Duration::from_millis(250)
+        );
+        assert_eq!(
+            repaint_interval(Some("None"), false),
+            std::time::Duration::from_secs(2)
+        );
+        assert_eq!(
+            repaint_interval(Some("InProgress"), true),
+            std::time::Duration::from_secs(5)
+        );
+        assert_eq!(
+            repaint_interval(None, true),
+            std::time::Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn first_matching_ban_rule_wins() {
+        let rules = vec![
+            BanRule {
+                position: Some("utility".to_owned()),
+                queue: Some(420),
+                champion: (53, "Blitzcrank".to_owned()),
+            },
+            BanRule {
+                position: Some("utility".to_owned()),
+                queue: None,
+                champion: (111, "Nautilus".to_owned()),
+            },
+        ];
+        assert_eq!(
+            rule_ban(&rules, Some("utility"), Some(420)),
+            Some((53, "Blitzcrank".to_owned()))
+        );
+        assert_eq!(
+            rule_ban(&rules, Some("utility"), Some(400)),
+            Some((111, "Nautilus".to_owned()))
+        );
+        assert_eq!(rule_ban(&rules, Some("top"), Some(420)), None);
+        assert_eq!(rule_ban(&rules, None, Some(420)), None);
+    }
+
+    #[test]
+    fn position_preferences_have_display_names() {
+        assert_eq!(preference_label("UTILITY"), "Support");
+        assert_eq!(preference_label("FILL"), "Fill");
+        assert_eq!(preference_label("UNSELECTED"), "None");
+        assert_eq!(preference_label(""), "None");
+    }
+
+    #[test]
+    fn autofill_is_an_unpreferred_position() {
+        let preferences = vec!["MIDDLE".to_owned(), "FILL".to_owned()];
+        assert!(!is_autofilled("middle", &preferences));
+        assert!(is_autofilled("utility", &preferences));
+    }
+
+    #[test]
+    fn my_champion_prefers_the_locked_one() {
+        let session = |champion_id: u32, intent: u32| {
+            serde_json::json!({
+                "localPlayerCellId": 1,
+                "myTeam": [
+                    { "cellId": 0, "championId": 22, "championPickIntent": 0 },
+                    { "cellId": 1, "championId": champion_id, "championPickIntent": intent }
+                ]
+            })
+        };
+        assert_eq!(my_champion(&session(103, 0)), Some(103));
+        assert_eq!(my_champion(&session(0, 99)), Some(99));
+        assert_eq!(my_champion(&session(0, 0)), None);
+    }
+
+    #[test]
+    fn a_hovered_pick_is_not_locked() {
+        let session = |completed: bool| {
+            serde_json::json!({
+                "localPlayerCellId": 1,
+                "myTeam": [{ "cellId": 1, "championId": 103, "championPickIntent": 103 }],
+                "actions": [[
+                    { "actorCellId": 0, "type": "pick", "completed": true, "championId": 22 },
+                    {
+                        "actorCellId": 1,
+                        "type": "pick",
+                        "completed": completed,
+                        "championId": 103
+                    }
+                ]]
+            })
+        };
+        assert!(!pick_locked(&session(false), 103));
+        assert!(pick_locked(&session(true), 103));
+        assert!(!pick_locked(&session(true), 22));
+    }
+
+    #[test]
+    fn a_single_pick_runs_through_the_pick_path() {
+        let picks = vec![(103, "Ahri".to_owned())];
+        assert_eq!(pick_candidates(&picks, PickMode::Priority, 0), picks);
+        assert_eq!(pick_candidates(&picks, PickMode::Separate, 0), picks);
+        // a second pick turn has no slot of its own
+        assert!(pick_candidates(&picks, PickMode::Separate, 1).is_empty());
+
+        let skipped = vec![(0, String::new())];
+        assert_eq!(pick_candidates(&skipped, PickMode::Priority, 0), skipped);
+    }
+
+    #[test]
+    fn turn_status_follows_my_actions() {
+        assert_eq!(
+            turn_status(true, false, false, None),
+            "Champion Selection — Waiting for ban turn"
+        );
+        assert_eq!(
+            turn_status(true, true, false, None),
+            "Champion Selection — Banning…"
+        );
+        assert_eq!(
+            turn_status(false, false, false, None),
+            "Champion Selection — Waiting for pick turn"
+        );
+        assert_eq!(
+            turn_status(false, false, true, None),
+            "Champion Selection — Picking…"
+        );
+        assert_eq!(
+            turn_status(false, false, false, Some("Ahri")),
+            "Champion Selection — Locked Ahri"
+        );
+    }
+
+    #[test]
+    fn profile_urls_use_the_site_region() {
+        assert_eq!(
+            profile_urls("EUW", "Some Name#EUW"),
+            Some((
+                "https://www.op.gg/summoners/euw/Some%20Name-EUW".to_owned(),
+                "https://u.gg/lol/profile/euw1/Some%20Name-EUW/overview".to_owned()
+            ))
+        );
+        assert_eq!(
+            profile_urls("LA2", "Name#LAS").unwrap().0,
+            "https://www.op.gg/summoners/las/Name-LAS"
+        );
+        assert_eq!(profile_urls("PBE", "Name#PBE"), None);
+    }
+
+    #[test]
+    fn free_rotation_champions_are_playable() {
+        assert!(is_playable(
+            &serde_json::json!({ "owned": false, "freeToPlay": true })
+        ));
+        assert!(is_playable(
+            &serde_json::json!({ "owned": true, "freeToPlay": false })
+        ));
+        assert!(!is_playable(
+            &serde_json::json!({ "owned": false, "freeToPlay": false })
+        ));
+    }
+
+    #[test]
+    fn patcher_state_shows_patching() {
+        assert!(is_patching(
+            &serde_json::json!({ "action": "Patching", "isUpToDate": false })
+        ));
+        assert!(is_patching(
+            &serde_json::json!({ "action": "Idle", "isUpToDate": false })
+        ));
+        assert!(!is_patching(
+            &serde_json::json!({ "action": "Idle", "isUpToDate": true })
+        ));
+        assert!(!is_patching(&serde_json::json!({})));
+    }
+
+    #[test]
+    fn inventory_marks_owned_and_free_champions() {
+        let champions = serde_json::json!([
+            { "id": 103, "ownership": { "owned": true } },
+            { "id": 266, "ownership": { "owned": false }, "freeToPlay": true },
+            { "id": 84, "ownership": { "owned": false } },
+            { "id": 0, "ownership": { "owned": true } },
+        ]);
+        let inventory = champion_inventory(&champions);
+        assert_eq!(inventory.get(&103), Some(&Availability::Owned));
+        assert_eq!(inventory.get(&266), Some(&Availability::FreeRotation));
+        assert_eq!(inventory.len(), 2);
+        assert!(champion_inventory(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn my_team_member_waits_for_my_cell() {
+        let draft: serde_json::Value = serde_json::from_str(MOCK_SCENARIOS[1].1).unwrap();
+        let mut session = draft["/lol-champ-select/v1/session"].clone();
+        let me = my_team_member(&session).unwrap();
+        assert_eq!(me.cellId, 2);
+        assert_eq!(me.assignedPosition, "middle");
+
+        session["myTeam"] = serde_json::json!([]);
+        assert!(my_team_member(&session).is_none());
+    }
+
+    #[test]
+    fn timer_is_parsed_from_fixture_sessions() {
+        let draft: serde_json::Value = serde_json::from_str(MOCK_SCENARIOS[1].1).unwrap();
+        let timer = Timer::from_session(&draft["/lol-champ-select/v1/session"]).unwrap();
+        assert_eq!(timer.phase, "BAN_PICK");
+        assert_eq!(timer.adjustedTimeLeftInPhase, 27000);
+        assert!(timer.allows_actions());
+
+        let aram: serde_json::Value = serde_json::from_str(MOCK_SCENARIOS[2].1).unwrap();
+        let timer = Timer::from_session(&aram["/lol-champ-select/v1/session"]).unwrap();
+        assert!(!timer.allows_actions());
+    }
+
+    #[test]
+    fn turn_order_is_parsed_from_fixture_session() {
+        let draft: serde_json::Value = serde_json::from_str(MOCK_SCENARIOS[1].1).unwrap();
+        let turns = turn_order(&draft["/lol-champ-select/v1/session"]);
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].len(), 5);
+        assert!(turns[0].iter().all(|action| action.r#type == "ban"));
+        assert_eq!(turns[1][0].actorCellId, 2);
+        assert!(turns[1][0].isAllyAction);
+
+        assert!(turn_order(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn enemy_champions_are_the_locked_ones() {
+        let session = serde_json::json!({
+            "theirTeam": [
+                { "championId": 157, "championPickIntent": 0 },
+                { "championId": 0, "championPickIntent": 238 },
+                {}
+            ]
+        });
+        assert_eq!(enemy_champions(&session), vec![Some(157), None, None]);
+        assert!(enemy_champions(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn counter_ban_matches_enemy_intent() {
+        let session = serde_json::json!({
+            "theirTeam": [
+                { "cellId": 5, "championPickIntent": 157 },
+                { "cellId": 6, "championId": 103 },
+                { "cellId": 7 }
+            ]
+        });
+        let watchlist = vec![(266, "Aatrox".to_owned()), (157, "Yasuo".to_owned())];
+        assert_eq!(
+            counter_ban(&session, &watchlist, |_| true),
+            Some((157, "Yasuo".to_owned()))
+        );
+        assert_eq!(counter_ban(&session, &watchlist, |id| id != 157), None);
+        assert_eq!(
+            counter_ban(&serde_json::json!({}), &watchlist, |_| true),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_champions_are_dropped() {
+        let champion = |id: u32, name: &str| Champion {
+            id,
+            name: name.to_owned(),
+            alias: String::new(),
+        };
+        let champions = validate_champions(vec![
+            champion(0, "Nobody"),
+            champion(1, "Annie"),
+            champion(1, "Olaf"),
+            champion(2, "annie"),
+            champion(3, "Galio"),
+        ]);
+        let ids: Vec<u32> = champions.iter().map(|champion| champion.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+    }
+
+    #[test]
+    fn live_game_summary_reads_the_active_player() {
+        let data = serde_json::json!({
+            "activePlayer": { "riotId": "Me#EUW" },
+            "allPlayers": [
+                { "riotId": "Them#EUW", "scores": { "kills": 9, "deaths": 0, "assists": 0, "creepScore": 10 } },
+                { "riotId": "Me#EUW", "scores": { "kills": 3, "deaths": 1, "assists": 4, "creepScore": 85 } }
+            ],
+            "gameData": { "gameTime": 754.3 }
+        });
+        assert_eq!(
+            live_game_summary(&data),
+            Some("In game 12:34 — 3/1/4, 85 CS".to_owned())
+        );
+        assert_eq!(live_game_summary(&serde_json::json!({})), None);
+    }
+
+    #[test]
+    fn gameflow_errors_are_not_idle() {
+        let session = serde_json::json!({ "phase": "ChampSelect" });
+        assert_eq!(gameflow_phase(200, &session), Ok(Some("ChampSelect")));
+        assert_eq!(
+            gameflow_phase(200, &serde_json::json!({ "phase": "None" })),
+            Ok(None)
+        );
+        assert_eq!(gameflow_phase(404, &serde_json::json!({})), Ok(None));
+
+        let error = serde_json::json!({ "httpStatus": 500, "message": "Internal error" });
+        assert_eq!(
+            gameflow_phase(500, &error),
+            Err("Internal error".to_owned())
+        );
+        assert!(gameflow_phase(200, &serde_json::json!([])).is_err());
+    }
+
+    #[test]
+    fn missing_timer_blocks_actions() {
+        let mut session: serde_json::Value = serde_json::from_str(MOCK_SCENARIOS[1].1).unwrap();
+        let session = &mut session["/lol-champ-select/v1/session"];
+        session.as_object_mut().unwrap().remove("timer");
+        assert_eq!(Timer::from_session(session), None);
+
+        session["timer"] = serde_json::json!({ "phase": "PLANNING" });
+        assert_eq!(Timer::from_session(session), None);
+    }
+
+    #[test]
+    fn kill_switch_keeps_queue_automation_off() {
+        let queue = QueueAutomation {
+            auto_accept: true,
+            pick_ban: true,
+            spells: true,
+        };
+        let settings = Settings {
+            queue_automation: HashMap::from([(420, queue)]),
+            ..Default::default()
+        };
+        assert_eq!(queue_override(&settings, Some(420), false), Some(queue));
+        assert_eq!(queue_override(&settings, Some(420), true), None);
+        assert_eq!(queue_override(&settings, Some(400), false), None);
+    }
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight() {
+        let time = |time| parse_clock_time(time).unwrap();
+        assert!(in_quiet_hours("23:00", "08:00", time("02:30")));
+        assert!(in_quiet_hours("23:00", "08:00", time("23:00")));
+        assert!(!in_quiet_hours("23:00", "08:00", time("08:00")));
+        assert!(!in_quiet_hours("23:00", "08:00", time("12:00")));
+        assert!(in_quiet_hours("13:00", "14:00", time("13:30")));
+        assert!(!in_quiet_hours("13:00", "14:00", time("14:30")));
+    }
+
+    #[test]
+    fn invalid_quiet_hours_never_pause() {
+        let noon = parse_clock_time("12:00").unwrap();
+        assert!(!in_quiet_hours("", "08:00", noon));
+        assert!(!in_quiet_hours("25:00", "08:00", noon));
+        assert!(!in_quiet_hours("12:00", "12:00", noon));
+    }
+
+    #[test]
+    fn picks_are_ordered_by_role_winrate() {
+        let stats: WinrateStats = serde_json::from_str(
+            r#"{
+                "103": { "middle": 51.0, "all": 50.0 },
+                "157": { "middle": 49.0, "top": 52.0, "all": 49.5 },
+                "1": { "all": 53.0 }
+            }"#,
+        )
+        .unwrap();
+        let picks = vec![
+            (157, "Yasuo".to_owned()),
+            (266, "Aatrox".to_owned()),
+            (103, "Ahri".to_owned()),
+            (1, "Annie".to_owned()),
+        ];
+
+        let mut middle = picks.clone();
+        order_by_winrate(&mut middle, &stats, Some("middle"));
+        let ids: Vec<u32> = middle.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![1, 103, 157, 266]);
+
+        let mut top = picks.clone();
+        order_by_winrate(&mut top, &stats, Some("top"));
+        let ids: Vec<u32> = top.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![1, 157, 103, 266]);
+    }
+
+    #[test]
+    fn champions_match_by_alias() {
+        let wukong = Champion {
+            id: 62,
+            name: "Wukong".to_owned(),
+            alias: "MonkeyKing".to_owned(),
+        };
+        assert!(wukong.matches(&clean_champion_name("wukong")));
+        assert!(wukong.matches(&clean_champion_name("monkeyking")));
+        assert!(wukong.matches_prefix(&clean_champion_name("monk")));
+        assert_eq!(wukong.label(true), "Wukong (MonkeyKing)");
+        assert_eq!(wukong.label(false), "Wukong");
+
+        let saved: Champion = serde_json::from_str(r#"{ "id": 62, "name": "Wukong" }"#).unwrap();
+        assert!(!saved.matches(""));
+    }
+
+    #[test]
+    fn positions_are_normalized() {
+        assert_eq!(normalize_position("JUNGLE"), Some("jungle".to_owned()));
+        assert_eq!(normalize_position(" jungle "), Some("jungle".to_owned()));
+        assert_eq!(normalize_position("Jungler"), Some("jungle".to_owned()));
+        assert_eq!(normalize_position("utility"), Some("utility".to_owned()));
+        assert_eq!(normalize_position("SUPPORT"), Some("utility".to_owned()));
+        assert_eq!(normalize_position("NONE"), None);
+        assert_eq!(normalize_position(""), None);
+    }
+
+    #[test]
+    fn jungle_spells_keep_the_partner_spell() {
+        let spells = |spell1: &str, spell2: &str| (spell1.to_owned(), spell2.to_owned());
+        assert_eq!(jungle_spells("Ignite", "Flash"), spells("Smite", "Flash"));
+        assert_eq!(jungle_spells("Ghost", "Heal"), spells("Ghost", "Smite"));
+        assert_eq!(jungle_spells("Flash", "Ghost"), spells("Flash", "Smite"));
+        assert_eq!(jungle_spells("Ghost", "Flash"), spells("Smite", "Flash"));
+        assert_eq!(jungle_spells("Ignite", "Heal"), spells("Smite", "Heal"));
+        assert_eq!(jungle_spells("Flash", "Smite"), spells("Flash", "Smite"));
+    }
+
+    #[test]
+    fn v1_settings_are_migrated() {
+        let v1 = r#"{
+            "pick_ban_selection": true,
+            "auto_accept": true,
+            "spell_selection": false,
+            "champion_picks": [[103, "Ahri"], [0, ""]],
+            "ban_picks": [157, "Yasuo"],
+            "spell1": "Flash",
+            "spell2": "Ignite"
+        }"#;
+
+        let settings = Settings::from_json(v1).expect("v1 settings should load");
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert!(settings.pick_ban_selection);
+        assert!(settings.auto_accept);
+        assert_eq!(settings.champion_picks[0], (103, "Ahri".to_owned()));
+        assert_eq!(settings.ban_picks, vec![(157, "Yasuo".to_owned())]);
+        assert_eq!(settings.spell1.as_deref(), Some("Flash"));
+        // fields that didn't exist yet get their defaults
+        assert!(settings.in_game_guard);
+        assert_eq!(settings.locale, "en_US");
+        assert_eq!(settings.trade_behavior, TradeBehavior::Off);
+    }
+
+    #[test]
+    fn ui_state_is_read_from_old_settings() {
+        let settings = r#"{
+            "version": 3,
+            "auto_accept": true,
+            "active_tab": 1,
+            "window_position": [120.0, 80.0]
+        }"#;
+        let ui_state: UiState = serde_json::from_str(settings).unwrap();
+        assert_eq!(ui_state.active_tab, 1);
+        assert_eq!(ui_state.window_position, Some((120.0, 80.0)));
+        assert_eq!(ui_state.ui_scale, 1.0);
+    }
+
+    #[test]
+    fn v2_ban_skip_is_migrated() {
+        let v2 = r#"{
+            "version": 2,
+            "champion_picks": [[103, "Ahri"]],
+            "ban_picks": [0, ""]
+        }"#;
+
+        let settings = Settings::from_json(v2).expect("v2 settings should load");
+        assert!(settings.ban_picks.is_empty());
+        assert!(settings.skip_ban);
+
+        let v2 = r#"{ "version": 2, "ban_picks": [157, "Yasuo"] }"#;
+        let settings = Settings::from_json(v2).expect("v2 settings should load");
+        assert_eq!(settings.ban_picks, vec![(157, "Yasuo".to_owned())]);
+        assert!(!settings.skip_ban);
+    }
+
+    #[test]
+    fn v3_ban_becomes_a_list() {
+        let v3 = r#"{
+            "version": 3,
+            "ban_picks": null,
+            "account_profiles": {
+                "puuid": { "summoner_name": "Me", "ban_picks": [157, "Yasuo"] }
+            }
+        }"#;
+
+        let settings = Settings::from_json(v3).expect("v3 settings should load");
+        assert!(settings.ban_picks.is_empty());
+        assert_eq!(
+            settings.account_profiles["puuid"].ban_picks,
+            vec![(157, "Yasuo".to_owned())]
+        );
+    }
+
+    #[test]
+    fn version_prefix_is_ignored() {
+        assert!(!is_outdated("1.2.0", "v1.2.0"));
+        assert!(is_outdated("1.2.0", "v1.2.1"));
+    }
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert!(is_outdated("1.2.0", "1.10.0"));
+        assert!(!is_outdated("1.10.0", "1.2.0"));
+    }
+
+    #[test]
+    fn equal_versions_are_up_to_date() {
+        assert!(!is_outdated("2.3.8", "2.3.8"));
+        assert!(!is_outdated("2.3", "2.3.0"));
+    }
+
+    #[test]
+    fn malformed_tag_is_not_outdated() {
+        assert!(!is_outdated("2.3.8", "latest"));
+        assert!(!is_outdated("2.3.8", ""));
+    }
+}